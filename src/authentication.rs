@@ -0,0 +1,360 @@
+use crate::{runtime, PrestinoError};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use log::debug;
+use reqwest::header::{HeaderMap, HeaderValue, WWW_AUTHENTICATE};
+use reqwest::Client;
+use serde::Deserialize;
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// How a client authenticates to the server.  The credentials are sent with every
+/// request for a statement: the statement itself, its next results, and cancelling
+/// it.  Since they would otherwise be sent in the clear, the client refuses to send
+/// them over plain HTTP unless `PrestinoClient::allow_insecure_authentication` is set.
+#[derive(Clone)]
+pub enum Authentication {
+    /// HTTP Basic authentication with a username and password.
+    Basic { username: String, password: String },
+    /// A bearer token, such as a JWT, which doesn't change.
+    Bearer(String),
+    /// A callback that returns the bearer token to use for each request, so that an
+    /// expiring token (such as a JWT) can be refreshed.
+    TokenProvider(Arc<dyn Fn() -> Result<String, PrestinoError> + Send + Sync>),
+    /// A token from the server's external (e.g. OAuth2) authentication, obtained
+    /// when the server first asks for it.
+    External(ExternalAuthentication),
+}
+
+impl Authentication {
+    pub fn basic(username: impl Into<String>, password: impl Into<String>) -> Self {
+        Self::Basic {
+            username: username.into(),
+            password: password.into(),
+        }
+    }
+
+    pub fn bearer(token: impl Into<String>) -> Self {
+        Self::Bearer(token.into())
+    }
+
+    pub fn token_provider(
+        provider: impl Fn() -> Result<String, PrestinoError> + Send + Sync + 'static,
+    ) -> Self {
+        Self::TokenProvider(Arc::new(provider))
+    }
+
+    /// Use the server's external authentication, calling `on_redirect` with the URL
+    /// the user must visit (e.g. in a browser) to authenticate.
+    pub fn external(
+        on_redirect: impl Fn(&str) -> Result<(), PrestinoError> + Send + Sync + 'static,
+    ) -> Self {
+        Self::External(ExternalAuthentication::new(on_redirect))
+    }
+
+    /// The value of the `Authorization` header for a request, if there is one yet.
+    pub(crate) fn header_value(&self) -> Result<Option<HeaderValue>, PrestinoError> {
+        let value = match self {
+            Self::Basic { username, password } => {
+                format!("Basic {}", BASE64.encode(format!("{username}:{password}")))
+            }
+            Self::Bearer(token) => format!("Bearer {token}"),
+            Self::TokenProvider(provider) => format!("Bearer {}", provider()?),
+            Self::External(external) => match external.token() {
+                Some(token) => format!("Bearer {token}"),
+                None => return Ok(None),
+            },
+        };
+        let mut value =
+            HeaderValue::from_str(&value).map_err(|_| PrestinoError::HeaderParseError)?;
+        value.set_sensitive(true);
+        Ok(Some(value))
+    }
+}
+
+type RedirectHandler = dyn Fn(&str) -> Result<(), PrestinoError> + Send + Sync;
+
+/// The server's external authentication flow, which the server asks for by
+/// responding 401 with a `WWW-Authenticate` header naming a redirect server and a
+/// token server.  The user is sent to the redirect server to authenticate (via the
+/// `on_redirect` callback), while the client polls the token server for the
+/// resulting token.  The token is then used for this and all later requests (by all
+/// clones of this client), until the server rejects it.
+#[derive(Clone)]
+pub struct ExternalAuthentication {
+    on_redirect: Arc<RedirectHandler>,
+    timeout: Duration,
+    token: Arc<Mutex<Option<String>>>,
+}
+
+impl ExternalAuthentication {
+    pub fn new(
+        on_redirect: impl Fn(&str) -> Result<(), PrestinoError> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            on_redirect: Arc::new(on_redirect),
+            timeout: Duration::from_secs(300),
+            token: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// How long to wait for the user to authenticate.  The default is 5 minutes.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// The cached token, if there is one.
+    pub fn token(&self) -> Option<String> {
+        self.token
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// If the headers of a 401 response ask for external authentication, carry it
+    /// out and cache the token, returning whether it did.
+    pub(crate) async fn authenticate(
+        &self,
+        http_client: &Client,
+        response_headers: &HeaderMap,
+    ) -> Result<bool, PrestinoError> {
+        let Some(challenge) = Challenge::from_headers(response_headers) else {
+            return Ok(false);
+        };
+        *self.token.lock().unwrap_or_else(PoisonError::into_inner) = None;
+        if let Some(redirect_uri) = &challenge.redirect_server {
+            (self.on_redirect)(redirect_uri)?;
+        }
+        let deadline = Instant::now() + self.timeout;
+        let poll = poll_token_server(http_client, challenge.token_server);
+        let token = runtime::run_until(Some(deadline), poll)
+            .await
+            .unwrap_or_else(|| {
+                Err(PrestinoError::ExternalAuthenticationError(
+                    "timed out waiting for the token".to_owned(),
+                ))
+            })?;
+        *self.token.lock().unwrap_or_else(PoisonError::into_inner) = Some(token);
+        Ok(true)
+    }
+}
+
+impl fmt::Debug for ExternalAuthentication {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExternalAuthentication")
+            .field("timeout", &self.timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The servers named by a `WWW-Authenticate` header asking for external
+/// authentication, like
+/// `Bearer x_redirect_server="https://...", x_token_server="https://..."`.
+#[derive(Debug, PartialEq)]
+struct Challenge {
+    redirect_server: Option<String>,
+    token_server: String,
+}
+
+impl Challenge {
+    fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        headers
+            .get_all(WWW_AUTHENTICATE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .find_map(Self::parse)
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        let params = value.trim().strip_prefix("Bearer")?;
+        let mut redirect_server = None;
+        let mut token_server = None;
+        for (name, value) in auth_params(params) {
+            match name {
+                "x_redirect_server" => redirect_server = Some(value),
+                "x_token_server" => token_server = Some(value),
+                _ => {}
+            }
+        }
+        Some(Self {
+            redirect_server,
+            token_server: token_server?,
+        })
+    }
+}
+
+/// Split the `name=value` parameters of a challenge, which are separated by commas.
+/// A value may be a quoted string, which can contain commas and backslash-escaped
+/// characters.  Anything that isn't a parameter is skipped.
+fn auth_params(params: &str) -> Vec<(&str, String)> {
+    let mut parsed = Vec::new();
+    let mut rest = params;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c == ',' || c.is_whitespace());
+        if rest.is_empty() {
+            return parsed;
+        }
+        let Some((name, after_name)) = rest.split_once('=') else {
+            return parsed;
+        };
+        let after_name = after_name.trim_start();
+        let value = if let Some(quoted) = after_name.strip_prefix('"') {
+            let mut value = String::new();
+            let mut chars = quoted.char_indices();
+            rest = "";
+            while let Some((index, c)) = chars.next() {
+                match c {
+                    '"' => {
+                        rest = &quoted[index + 1..];
+                        break;
+                    }
+                    '\\' => value.extend(chars.next().map(|(_, escaped)| escaped)),
+                    _ => value.push(c),
+                }
+            }
+            value
+        } else {
+            let (value, after_value) = after_name.split_once(',').unwrap_or((after_name, ""));
+            rest = after_value;
+            value.trim().to_owned()
+        };
+        // A name with a space in it is really the end of something else, like a
+        // second scheme.
+        let name = name.trim();
+        if !name.contains(char::is_whitespace) {
+            parsed.push((name, value));
+        }
+    }
+}
+
+/// A response from the token server: the token, an error, or where to poll next.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TokenPoll {
+    token: Option<String>,
+    error: Option<String>,
+    next_uri: Option<String>,
+}
+
+/// How long to wait before following the token server's next uri.
+const TOKEN_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Poll the token server until it sends the token or an error.  The caller bounds
+/// how long this takes.
+async fn poll_token_server(
+    http_client: &Client,
+    mut token_uri: String,
+) -> Result<String, PrestinoError> {
+    loop {
+        debug!("Polling for external authentication token: {token_uri}");
+        let response = http_client.get(&token_uri).send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let message = response.text().await?;
+            return Err(PrestinoError::from_status_code(status.as_u16(), message));
+        }
+        match response.json::<TokenPoll>().await? {
+            TokenPoll {
+                token: Some(token), ..
+            } => return Ok(token),
+            TokenPoll {
+                error: Some(error), ..
+            } => return Err(PrestinoError::ExternalAuthenticationError(error)),
+            TokenPoll {
+                next_uri: Some(next_uri),
+                ..
+            } => {
+                token_uri = next_uri;
+                runtime::sleep(TOKEN_POLL_INTERVAL).await;
+            }
+            _ => {
+                return Err(PrestinoError::ExternalAuthenticationError(
+                    "token server sent neither a token nor a next uri".to_owned(),
+                ))
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Authentication {
+    /// Show the kind of authentication, but not the credentials.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Basic { username, .. } => f
+                .debug_struct("Basic")
+                .field("username", username)
+                .finish_non_exhaustive(),
+            Self::Bearer(_) => f.write_str("Bearer(..)"),
+            Self::TokenProvider(_) => f.write_str("TokenProvider(..)"),
+            Self::External(external) => f.debug_tuple("External").field(external).finish(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_value() {
+        let header_value = |authentication: Authentication| {
+            authentication
+                .header_value()
+                .unwrap()
+                .map(|value| value.to_str().unwrap().to_owned())
+        };
+        assert_eq!(
+            header_value(Authentication::basic("Aladdin", "open sesame")).as_deref(),
+            Some("Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ==")
+        );
+        assert_eq!(
+            header_value(Authentication::bearer("a.b.c")).as_deref(),
+            Some("Bearer a.b.c")
+        );
+        let provider = Authentication::token_provider(|| Ok("fresh".to_owned()));
+        assert_eq!(header_value(provider).as_deref(), Some("Bearer fresh"));
+        assert_eq!(header_value(Authentication::external(|_| Ok(()))), None);
+        assert!(Authentication::bearer("bad\ntoken").header_value().is_err());
+    }
+
+    #[test]
+    fn test_parse_challenge() {
+        assert_eq!(
+            Challenge::parse(
+                r#"Bearer x_redirect_server="https://a/initiate/1", x_token_server="https://a/token/1""#
+            ),
+            Some(Challenge {
+                redirect_server: Some("https://a/initiate/1".to_owned()),
+                token_server: "https://a/token/1".to_owned(),
+            })
+        );
+        assert_eq!(
+            Challenge::parse(r#"Bearer x_token_server="https://a/token/1""#),
+            Some(Challenge {
+                redirect_server: None,
+                token_server: "https://a/token/1".to_owned(),
+            })
+        );
+        assert_eq!(
+            Challenge::parse(
+                r#"Bearer realm="Trino", x_redirect_server="https://a/initiate?state=x,y", x_token_server = "https://a/\"token\"""#
+            ),
+            Some(Challenge {
+                redirect_server: Some("https://a/initiate?state=x,y".to_owned()),
+                token_server: "https://a/\"token\"".to_owned(),
+            })
+        );
+        assert_eq!(Challenge::parse(r#"Basic realm="Trino""#), None);
+        assert_eq!(Challenge::parse("Bearer realm=\"Trino\""), None);
+    }
+
+    #[test]
+    fn test_debug_hides_credentials() {
+        let debug = format!("{:?}", Authentication::basic("me", "secret"));
+        assert!(debug.contains("me") && !debug.contains("secret"), "{debug}");
+        let debug = format!("{:?}", Authentication::bearer("secret"));
+        assert!(!debug.contains("secret"), "{debug}");
+    }
+}
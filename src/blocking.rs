@@ -0,0 +1,257 @@
+//! A blocking client, for synchronous code that doesn't want to run an async runtime
+//! itself, in the spirit of `reqwest::blocking`.  Enabled by the `blocking` feature.
+//!
+//! Each client runs its requests on its own small tokio runtime, so it must not be
+//! used from within an async runtime (which will panic).
+//!
+//! ```no_run
+//! use prestino::blocking::PrestinoClient;
+//!
+//! let client = PrestinoClient::trino("http://localhost:8080").user("me");
+//! for row in client.execute::<(i64, String)>("SELECT 1, 'a'")?.rows() {
+//!     let (a, b) = row?;
+//!     println!("{a} {b}");
+//! }
+//! # Ok::<(), prestino::PrestinoError>(())
+//! ```
+
+use crate::results::Column;
+use crate::{Headers, PrestinoError, QueryStats};
+use log::warn;
+use serde::de::DeserializeOwned;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::runtime::{Builder, Runtime};
+
+/// A blocking version of [`crate::PrestinoClient`].  Configure it like the async
+/// client (or make one from an async client), then run statements with it.
+#[derive(Debug, Clone)]
+pub struct PrestinoClient {
+    inner: crate::PrestinoClient,
+    runtime: Arc<Runtime>,
+}
+
+impl PrestinoClient {
+    /// Create a Presto client with no headers set.
+    pub fn presto(base_url: impl Into<String>) -> Self {
+        crate::PrestinoClient::presto(base_url).into()
+    }
+
+    /// Create a Trino client with no headers set.
+    pub fn trino(base_url: impl Into<String>) -> Self {
+        crate::PrestinoClient::trino(base_url).into()
+    }
+
+    /// Create a client with the headers set.  The headers fork will determine the client's fork.
+    pub fn with_headers(base_url: impl Into<String>, headers: Headers) -> Self {
+        crate::PrestinoClient::with_headers(base_url, headers).into()
+    }
+
+    pub fn headers(&self) -> &Headers {
+        self.inner.headers()
+    }
+
+    pub fn headers_mut(&mut self) -> &mut Headers {
+        self.inner.headers_mut()
+    }
+
+    /// Convenience function to set the user header.  Not needed if it's already set.
+    pub fn user(mut self, user: &str) -> Self {
+        self.inner = self.inner.user(user);
+        self
+    }
+
+    /// The async client this runs statements with.
+    pub fn async_client(&self) -> &crate::PrestinoClient {
+        &self.inner
+    }
+
+    /// Begin execution of a statement, returning a StatementExecutor to continue execution.
+    pub fn execute<T: DeserializeOwned>(
+        &self,
+        statement: impl Into<String>,
+    ) -> Result<StatementExecutor<T>, PrestinoError> {
+        let executor = self.runtime.block_on(self.inner.execute(statement))?;
+        Ok(StatementExecutor::new(executor, self.runtime.clone()))
+    }
+
+    /// Begin execution of a statement, returning a StatementExecutor to continue execution.
+    pub fn execute_with_headers<T: DeserializeOwned>(
+        &self,
+        statement: impl Into<String>,
+        headers: &Headers,
+    ) -> Result<StatementExecutor<T>, PrestinoError> {
+        let executor = self
+            .runtime
+            .block_on(self.inner.execute_with_headers(statement, headers))?;
+        Ok(StatementExecutor::new(executor, self.runtime.clone()))
+    }
+
+    /// A convenience function to retrieve all the rows for the statement into a single Vec.
+    pub fn execute_collect<T: DeserializeOwned>(
+        &self,
+        statement: impl Into<String>,
+    ) -> Result<Vec<T>, PrestinoError> {
+        self.runtime.block_on(self.inner.execute_collect(statement))
+    }
+
+    /// A convenience function to retrieve all the rows for the statement into a single Vec.
+    pub fn execute_collect_with_headers<T: DeserializeOwned>(
+        &self,
+        statement: impl Into<String>,
+        headers: &Headers,
+    ) -> Result<Vec<T>, PrestinoError> {
+        self.runtime
+            .block_on(self.inner.execute_collect_with_headers(statement, headers))
+    }
+}
+
+impl From<crate::PrestinoClient> for PrestinoClient {
+    /// Run the async client's statements blocking.  Panics if the runtime for the
+    /// client can't be created.
+    fn from(inner: crate::PrestinoClient) -> Self {
+        let runtime = Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("failed to create the runtime for the blocking client");
+        Self {
+            inner,
+            runtime: Arc::new(runtime),
+        }
+    }
+}
+
+/// A blocking version of [`crate::StatementExecutor`], whose results are read with
+/// iterators instead of streams.  As with the async executor, dropping it before the
+/// statement finishes cancels the statement, unless `cancel_on_drop(false)` is set.
+pub struct StatementExecutor<T: DeserializeOwned> {
+    /// Only `None` once dropped.
+    inner: Option<crate::StatementExecutor<T>>,
+    runtime: Arc<Runtime>,
+}
+
+impl<T: DeserializeOwned> StatementExecutor<T> {
+    fn new(inner: crate::StatementExecutor<T>, runtime: Arc<Runtime>) -> Self {
+        Self {
+            inner: Some(inner),
+            runtime,
+        }
+    }
+
+    fn inner(&self) -> &crate::StatementExecutor<T> {
+        self.inner.as_ref().expect("executor is only taken on drop")
+    }
+
+    fn inner_mut(&mut self) -> &mut crate::StatementExecutor<T> {
+        self.inner.as_mut().expect("executor is only taken on drop")
+    }
+
+    fn map_inner(
+        mut self,
+        f: impl FnOnce(crate::StatementExecutor<T>) -> crate::StatementExecutor<T>,
+    ) -> Self {
+        self.inner = self.inner.take().map(f);
+        self
+    }
+
+    /// See [`crate::StatementExecutor::deserialize_by_name`].
+    pub fn deserialize_by_name(self) -> Self {
+        self.map_inner(crate::StatementExecutor::deserialize_by_name)
+    }
+
+    pub fn is_deserialized_by_name(&self) -> bool {
+        self.inner().is_deserialized_by_name()
+    }
+
+    /// See [`crate::StatementExecutor::cancel_on_drop`].
+    pub fn cancel_on_drop(self, cancel_on_drop: bool) -> Self {
+        self.map_inner(|inner| inner.cancel_on_drop(cancel_on_drop))
+    }
+
+    pub fn is_cancelled_on_drop(&self) -> bool {
+        self.inner().is_cancelled_on_drop()
+    }
+
+    /// See [`crate::StatementExecutor::timeout`].
+    pub fn timeout(self, timeout: Duration) -> Self {
+        self.map_inner(|inner| inner.timeout(timeout))
+    }
+
+    /// See [`crate::StatementExecutor::progress_timeout`].
+    pub fn progress_timeout(self, timeout: Duration) -> Self {
+        self.map_inner(|inner| inner.progress_timeout(timeout))
+    }
+
+    pub fn id(&self) -> &str {
+        self.inner().id()
+    }
+
+    pub fn info_uri(&self) -> &str {
+        self.inner().info_uri()
+    }
+
+    pub fn columns(&self) -> Option<&[Column]> {
+        self.inner().columns()
+    }
+
+    pub fn stats(&self) -> &QueryStats {
+        self.inner().stats()
+    }
+
+    /// Cancel execution of this statement.  If the query is already finished,
+    /// return PrestinoError::QueryFinishedError with the query id.
+    pub fn cancel(mut self) -> Result<(), PrestinoError> {
+        let inner = self.inner.take().expect("executor is only taken on drop");
+        self.runtime.block_on(inner.cancel())
+    }
+
+    /// Get the next page of rows, which may be empty, or `None` when there are no more.
+    pub fn next_response(&mut self) -> Option<Result<Vec<T>, PrestinoError>> {
+        let runtime = self.runtime.clone();
+        runtime.block_on(self.inner_mut().next_response())
+    }
+
+    /// Iterate over the non-empty pages of rows.
+    pub fn batches(mut self) -> impl Iterator<Item = Result<Vec<T>, PrestinoError>> {
+        std::iter::from_fn(move || loop {
+            match self.next_response()? {
+                Ok(rows) if rows.is_empty() => continue,
+                response => return Some(response),
+            }
+        })
+    }
+
+    /// Iterate over the rows.
+    pub fn rows(self) -> impl Iterator<Item = Result<T, PrestinoError>> {
+        let mut batches = self.batches();
+        let mut rows = VecDeque::new();
+        std::iter::from_fn(move || loop {
+            if let Some(row) = rows.pop_front() {
+                return Some(Ok(row));
+            }
+            match batches.next()? {
+                Ok(batch) => rows.extend(batch),
+                Err(err) => return Some(Err(err)),
+            }
+        })
+    }
+}
+
+impl<T: DeserializeOwned> Drop for StatementExecutor<T> {
+    fn drop(&mut self) {
+        // The async executor would cancel in the background, but nothing runs this
+        // client's runtime in the background, so cancel before returning.
+        let Some(inner) = self.inner.take() else {
+            return;
+        };
+        if !inner.is_cancelled_on_drop() {
+            return;
+        }
+        let id = inner.id().to_owned();
+        match self.runtime.block_on(inner.cancel()) {
+            Ok(()) | Err(PrestinoError::QueryFinishedError(_)) => {}
+            Err(err) => warn!("Failed to cancel statement {id}: {err}"),
+        }
+    }
+}
@@ -0,0 +1,419 @@
+use crate::headers::check_visible_ascii;
+use crate::{Authentication, Headers, PrestinoClient, PrestinoClientBuilder, PrestinoError};
+use log::debug;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// The client settings of the Trino CLI, from its config file and environment
+/// variables, so that a program picks up the same server, user, catalog and so on
+/// as the CLI in the same shell.
+///
+/// The config file is `~/.trino_config`, or the file named by `TRINO_CONFIG`.  Like
+/// the CLI's, it is a properties file of the CLI's long options, like
+/// `catalog=hive`.  Options this doesn't use (e.g. `output-format`) are ignored.
+/// The options used, and the environment variables for them, are:
+///
+/// | Option          | Variable             | Format                         |
+/// |-----------------|----------------------|--------------------------------|
+/// | `server`        | `TRINO_SERVER`       | `host:port`, or a URL          |
+/// | `user`          | `TRINO_USER`         |                                |
+/// | `password`      | `TRINO_PASSWORD`     | `true` in the file (see below) |
+/// | `access-token`  | `TRINO_ACCESS_TOKEN` |                                |
+/// | `catalog`       | `TRINO_CATALOG`      |                                |
+/// | `schema`        | `TRINO_SCHEMA`       |                                |
+/// | `source`        | `TRINO_SOURCE`       |                                |
+/// | `timezone`      | `TRINO_TIMEZONE`     |                                |
+/// | `client-info`   | `TRINO_CLIENT_INFO`  |                                |
+/// | `session`       | `TRINO_SESSION`      | `name=value,name=value`        |
+/// | `client-tags`   | `TRINO_CLIENT_TAGS`  | `tag,tag`                      |
+///
+/// As with the CLI, `password=true` in the file asks for a password, which must
+/// then be in `TRINO_PASSWORD`, since there is no one to prompt for it.
+///
+/// Environment variables take precedence over the config file, which takes
+/// precedence over the defaults: `localhost:8080` for the server, and `USER` for the
+/// user.  The loaded settings can then be changed on the `CliConfig`, or on the
+/// client and headers made from it.
+#[derive(Clone, Default, PartialEq)]
+pub struct CliConfig {
+    pub server: Option<String>,
+    pub user: Option<String>,
+    pub password: Option<String>,
+    pub access_token: Option<String>,
+    pub catalog: Option<String>,
+    pub schema: Option<String>,
+    pub source: Option<String>,
+    pub timezone: Option<String>,
+    pub client_info: Option<String>,
+    pub session_properties: BTreeMap<String, String>,
+    pub client_tags: Vec<String>,
+}
+
+const DEFAULT_SERVER: &str = "localhost:8080";
+
+impl CliConfig {
+    /// Load the config file (if there is one) and environment variables.
+    pub fn load() -> Result<Self, PrestinoError> {
+        Self::load_with(|name| std::env::var(name).ok())
+    }
+
+    /// Load the config file and variables, getting each variable with `var`.
+    pub(crate) fn load_with(var: impl Fn(&str) -> Option<String>) -> Result<Self, PrestinoError> {
+        let path = match var("TRINO_CONFIG") {
+            Some(path) => Some(PathBuf::from(path)),
+            None => var("HOME").map(|home| Path::new(&home).join(".trino_config")),
+        };
+        let (mut config, asks_for_password) = match path {
+            Some(path) => Self::read_file(&path)?,
+            None => (Self::default(), false),
+        };
+        config.apply_env(&var)?;
+        if asks_for_password && config.password.is_none() {
+            return Err(PrestinoError::ConfigError(
+                "the config file asks for a password, but TRINO_PASSWORD is not set".to_owned(),
+            ));
+        }
+        if config.user.is_none() {
+            config.user = var("USER");
+        }
+        Ok(config)
+    }
+
+    /// Read a config file, returning its settings and whether it asks for a
+    /// password.  A missing file is empty.
+    fn read_file(path: &Path) -> Result<(Self, bool), PrestinoError> {
+        let properties = match fs::read_to_string(path) {
+            Ok(properties) => properties,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok((Self::default(), false)),
+            Err(err) => {
+                return Err(PrestinoError::ConfigError(format!(
+                    "could not read {}: {err}",
+                    path.display()
+                )))
+            }
+        };
+        Self::from_properties(&properties)
+            .map_err(|message| PrestinoError::ConfigError(format!("{}: {message}", path.display())))
+    }
+
+    fn from_properties(properties: &str) -> Result<(Self, bool), String> {
+        let mut config = Self::default();
+        let mut asks_for_password = false;
+        for (index, line) in properties.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with('!') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once(['=', ':']) else {
+                return Err(format!("line {}: expected option=value", index + 1));
+            };
+            let (key, value) = (key.trim(), value.trim());
+            if key == "password" {
+                asks_for_password = value
+                    .parse()
+                    .map_err(|_| format!("line {}: password must be true or false", index + 1))?;
+            } else if !config.set(key, value)? {
+                debug!("Ignoring CLI option {key}");
+            }
+        }
+        Ok((config, asks_for_password))
+    }
+
+    /// Override the settings with any environment variables.
+    fn apply_env(&mut self, var: &impl Fn(&str) -> Option<String>) -> Result<(), PrestinoError> {
+        for key in OPTIONS {
+            let name = format!("TRINO_{}", key.to_ascii_uppercase().replace('-', "_"));
+            if let Some(value) = var(&name) {
+                self.set(key, &value)
+                    .map_err(|message| PrestinoError::ConfigError(format!("{name}: {message}")))?;
+            }
+        }
+        self.password = var("TRINO_PASSWORD");
+        Ok(())
+    }
+
+    /// Set an option, returning whether it is one that is used.
+    fn set(&mut self, key: &str, value: &str) -> Result<bool, String> {
+        if key != "server" && key != "access-token" {
+            check_visible_ascii(key, value)?;
+        }
+        let value = value.to_owned();
+        match key {
+            "server" => self.server = Some(value),
+            "user" => self.user = Some(value),
+            "access-token" => self.access_token = Some(value),
+            "catalog" => self.catalog = Some(value),
+            "schema" => self.schema = Some(value),
+            "source" => self.source = Some(value),
+            "timezone" => self.timezone = Some(value),
+            "client-info" => self.client_info = Some(value),
+            "session" => {
+                self.session_properties = split_list(&value)
+                    .map(|property| match property.split_once('=') {
+                        Some((name, value)) => {
+                            Ok((name.trim().to_owned(), value.trim().to_owned()))
+                        }
+                        None => Err(format!("session property '{property}' is not name=value")),
+                    })
+                    .collect::<Result<_, _>>()?
+            }
+            "client-tags" => self.client_tags = split_list(&value).map(str::to_owned).collect(),
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// The URL of the server.  As in the CLI, a server given as `host:port` uses
+    /// https for port 443, and http otherwise.
+    pub fn base_url(&self) -> String {
+        let server = self.server.as_deref().unwrap_or(DEFAULT_SERVER);
+        if server.contains("://") {
+            return server.to_owned();
+        }
+        if server.ends_with(":443") {
+            format!("https://{server}")
+        } else {
+            format!("http://{server}")
+        }
+    }
+
+    /// The Trino headers for the user, catalog, schema, source, time zone, client
+    /// info, session properties and client tags.  These must only contain visible
+    /// ASCII characters, as they do when loaded; otherwise this function will panic.
+    pub fn headers(&self) -> Headers {
+        let mut headers = Headers::trino();
+        if let Some(user) = &self.user {
+            headers.set_user(user);
+        }
+        if let Some(catalog) = &self.catalog {
+            headers.set_catalog(catalog);
+        }
+        if let Some(schema) = &self.schema {
+            headers.set_schema(schema);
+        }
+        if let Some(source) = &self.source {
+            headers.set_source(source);
+        }
+        if let Some(timezone) = &self.timezone {
+            headers.set_timezone(timezone);
+        }
+        if let Some(client_info) = &self.client_info {
+            headers.set_client_info(client_info);
+        }
+        for (name, value) in &self.session_properties {
+            headers.set_session(name, value);
+        }
+        if !self.client_tags.is_empty() {
+            let tags: Vec<&str> = self.client_tags.iter().map(String::as_str).collect();
+            headers.set_client_tags(&tags);
+        }
+        headers
+    }
+
+    /// A client builder for the server, with the headers set.
+    pub fn client_builder(&self) -> PrestinoClientBuilder {
+        PrestinoClientBuilder::new(self.base_url(), self.headers())
+    }
+
+    /// A client for the server, with the headers set, and authenticating with the
+    /// access token or password if there is one.
+    pub fn client(&self) -> Result<PrestinoClient, PrestinoError> {
+        let client = self.client_builder().build()?;
+        let authentication = match (&self.access_token, &self.password) {
+            (Some(token), _) => Authentication::bearer(token),
+            (None, Some(password)) => {
+                Authentication::basic(self.user.as_deref().unwrap_or_default(), password)
+            }
+            (None, None) => return Ok(client),
+        };
+        Ok(client.authentication(authentication))
+    }
+}
+
+/// The options that can be set from the environment, other than the password.
+const OPTIONS: [&str; 10] = [
+    "server",
+    "user",
+    "access-token",
+    "catalog",
+    "schema",
+    "source",
+    "timezone",
+    "client-info",
+    "session",
+    "client-tags",
+];
+
+fn split_list(value: &str) -> impl Iterator<Item = &str> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+}
+
+impl std::fmt::Debug for CliConfig {
+    /// Show everything but the password and access token.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CliConfig")
+            .field("server", &self.server)
+            .field("user", &self.user)
+            .field("password", &self.password.as_ref().map(|_| ".."))
+            .field("access_token", &self.access_token.as_ref().map(|_| ".."))
+            .field("catalog", &self.catalog)
+            .field("schema", &self.schema)
+            .field("source", &self.source)
+            .field("timezone", &self.timezone)
+            .field("client_info", &self.client_info)
+            .field("session_properties", &self.session_properties)
+            .field("client_tags", &self.client_tags)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use maplit::{btreemap, hashmap};
+    use std::collections::HashMap;
+
+    fn write_config(name: &str, properties: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("prestino-{name}-{}.properties", std::process::id()));
+        fs::write(&path, properties).unwrap();
+        path
+    }
+
+    fn load(env: HashMap<&str, String>) -> Result<CliConfig, PrestinoError> {
+        CliConfig::load_with(|name| env.get(name).cloned())
+    }
+
+    #[test]
+    fn test_config_file() {
+        let path = write_config(
+            "file",
+            "# defaults for the analysts\n\
+             server = https://trino.example.com\n\
+             user=analyst\n\
+             catalog: hive\n\
+             schema=web\n\
+             session=query_max_run_time=1h, join_distribution_type=BROADCAST\n\
+             client-tags=adhoc,web\n\
+             output-format=ALIGNED\n",
+        );
+        let config = load(hashmap! {"TRINO_CONFIG" => path.display().to_string()}).unwrap();
+        assert_eq!(
+            config,
+            CliConfig {
+                server: Some("https://trino.example.com".to_owned()),
+                user: Some("analyst".to_owned()),
+                catalog: Some("hive".to_owned()),
+                schema: Some("web".to_owned()),
+                session_properties: btreemap! {
+                    "join_distribution_type".to_owned() => "BROADCAST".to_owned(),
+                    "query_max_run_time".to_owned() => "1h".to_owned(),
+                },
+                client_tags: vec!["adhoc".to_owned(), "web".to_owned()],
+                ..CliConfig::default()
+            }
+        );
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_precedence() {
+        let path = write_config("precedence", "catalog=hive\nschema=web\nuser=analyst\n");
+        let config = load(hashmap! {
+            "TRINO_CONFIG" => path.display().to_string(),
+            "TRINO_SCHEMA" => "events".to_owned(),
+            "TRINO_CLIENT_TAGS" => "etl".to_owned(),
+            "USER" => "login".to_owned(),
+        })
+        .unwrap();
+        assert_eq!(config.catalog.as_deref(), Some("hive"));
+        assert_eq!(config.schema.as_deref(), Some("events"));
+        assert_eq!(config.user.as_deref(), Some("analyst"));
+        assert_eq!(config.client_tags, vec!["etl".to_owned()]);
+        fs::remove_file(path).unwrap();
+
+        // Without the file, the defaults.
+        let config = load(hashmap! {
+            "HOME" => "/nonexistent".to_owned(),
+            "USER" => "login".to_owned(),
+        })
+        .unwrap();
+        assert_eq!(config.user.as_deref(), Some("login"));
+        assert_eq!(config.base_url(), "http://localhost:8080");
+    }
+
+    #[test]
+    fn test_password() {
+        let path = write_config("password", "server=trino.example.com:443\npassword=true\n");
+        let env = hashmap! {"TRINO_CONFIG" => path.display().to_string()};
+        let err = load(env.clone()).unwrap_err();
+        assert!(matches!(err, PrestinoError::ConfigError(_)), "{err:?}");
+
+        let mut env = env;
+        env.insert("TRINO_PASSWORD", "secret".to_owned());
+        let config = load(env).unwrap();
+        assert_eq!(config.password.as_deref(), Some("secret"));
+        assert_eq!(config.base_url(), "https://trino.example.com:443");
+        assert!(!format!("{config:?}").contains("secret"));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let path = write_config("invalid", "catalog=hive\nschema\n");
+        let err = load(hashmap! {"TRINO_CONFIG" => path.display().to_string()}).unwrap_err();
+        assert!(
+            matches!(&err, PrestinoError::ConfigError(message) if message.ends_with("line 2: expected option=value")),
+            "{err:?}"
+        );
+        fs::remove_file(path).unwrap();
+
+        let err = load(hashmap! {
+            "HOME" => "/nonexistent".to_owned(),
+            "TRINO_SESSION" => "query_max_run_time".to_owned(),
+        })
+        .unwrap_err();
+        assert!(
+            matches!(&err, PrestinoError::ConfigError(message) if message.starts_with("TRINO_SESSION:")),
+            "{err:?}"
+        );
+
+        let err = load(hashmap! {
+            "HOME" => "/nonexistent".to_owned(),
+            "TRINO_USER" => "jos\u{e9}".to_owned(),
+        })
+        .unwrap_err();
+        assert!(
+            matches!(&err, PrestinoError::ConfigError(message) if message.starts_with("TRINO_USER:")),
+            "{err:?}"
+        );
+    }
+
+    #[test]
+    fn test_client() {
+        let config = CliConfig {
+            server: Some("localhost:8081".to_owned()),
+            user: Some("me".to_owned()),
+            catalog: Some("hive".to_owned()),
+            client_tags: vec!["etl".to_owned()],
+            session_properties: btreemap! {"a".to_owned() => "1".to_owned()},
+            ..CliConfig::default()
+        };
+        let client = config.client().unwrap();
+        assert_eq!(client.base_url(), "http://localhost:8081");
+        let header_map = client.headers().build().unwrap();
+        for (name, value) in [
+            ("x-trino-user", "me"),
+            ("x-trino-catalog", "hive"),
+            ("x-trino-client-tags", "etl"),
+            ("x-trino-session", "a=1"),
+        ] {
+            assert_eq!(header_map[name], value, "{name}");
+        }
+    }
+}
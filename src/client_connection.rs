@@ -1,13 +1,22 @@
 use crate::results::QueryResults;
-use crate::{Headers, PrestinoError};
-use log::debug;
-use reqwest::{Client, Response};
+use crate::runtime;
+use crate::{Authentication, Headers, PrestinoError, RetryPolicy};
+use log::{debug, warn};
+use reqwest::header::AUTHORIZATION;
+use reqwest::{Client, Method, RequestBuilder, Response, StatusCode, Url};
 use serde::de::DeserializeOwned;
+use std::sync::{Arc, Mutex, PoisonError};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(crate) struct ClientConnection {
     pub(crate) headers: Headers,
     pub(crate) http_client: Client,
+    /// The headers of the Session this statement runs in, if any.  Changes the
+    /// server requests are applied here as well, so they outlive this connection.
+    pub(crate) session_headers: Option<Arc<Mutex<Headers>>>,
+    pub(crate) retry_policy: RetryPolicy,
+    pub(crate) authentication: Option<Authentication>,
+    pub(crate) allow_insecure_authentication: bool,
 }
 
 impl ClientConnection {
@@ -17,55 +26,136 @@ impl ClientConnection {
         base_url: &str,
         statement: impl Into<String>,
     ) -> Result<QueryResults<T>, PrestinoError> {
+        let uri = format!("{}/v1/statement", base_url);
         let response = self
-            .http_client
-            .post(format!("{}/v1/statement", base_url))
-            .headers(self.headers.build()?)
-            .body(statement.into())
-            .send()
+            .send(Method::POST, &uri, Some(statement.into()))
             .await?;
 
         self.parse_response(response).await
     }
 
+    /// Get the results at `next_uri`, retrying failures as the retry policy allows.
     pub async fn get_next_results<T: DeserializeOwned>(
         &mut self,
         next_uri: &str,
+    ) -> Result<QueryResults<T>, PrestinoError> {
+        let mut attempt = 1;
+        loop {
+            match self.try_get_next_results(next_uri).await {
+                Err(err) => self.wait_to_retry(err, attempt).await?,
+                result => return result,
+            }
+            attempt += 1;
+        }
+    }
+
+    async fn try_get_next_results<T: DeserializeOwned>(
+        &mut self,
+        next_uri: &str,
     ) -> Result<QueryResults<T>, PrestinoError> {
         debug!("Getting next results: {}", next_uri);
-        let response = self
-            .http_client
-            .get(next_uri)
-            .headers(self.headers.build()?)
-            .send()
-            .await?;
+        let response = self.send(Method::GET, next_uri, None).await?;
         self.parse_response(response).await
     }
 
+    /// Start a request with the headers and any authentication.
+    fn request(&self, method: Method, uri: &str) -> Result<RequestBuilder, PrestinoError> {
+        let mut request = self
+            .http_client
+            .request(method, uri)
+            .headers(self.headers.build()?);
+        if let Some(authentication) = &self.authentication {
+            let secure = Url::parse(uri).is_ok_and(|url| url.scheme() == "https");
+            if !secure && !self.allow_insecure_authentication {
+                return Err(PrestinoError::InsecureAuthenticationError(uri.to_owned()));
+            }
+            if let Some(value) = authentication.header_value()? {
+                request = request.header(AUTHORIZATION, value);
+            }
+        }
+        Ok(request)
+    }
+
+    /// Send a request.  If the server asks for external authentication, carry it out
+    /// and send the request again.
+    async fn send(
+        &self,
+        method: Method,
+        uri: &str,
+        body: Option<String>,
+    ) -> Result<Response, PrestinoError> {
+        let build = |request: RequestBuilder| match &body {
+            Some(body) => request.body(body.clone()),
+            None => request,
+        };
+        let response = build(self.request(method.clone(), uri)?).send().await?;
+        if response.status() != StatusCode::UNAUTHORIZED {
+            return Ok(response);
+        }
+        let Some(Authentication::External(external)) = &self.authentication else {
+            return Ok(response);
+        };
+        if !external
+            .authenticate(&self.http_client, response.headers())
+            .await?
+        {
+            return Ok(response);
+        }
+        Ok(build(self.request(method, uri)?).send().await?)
+    }
+
     async fn parse_response<T: DeserializeOwned>(
         &mut self,
         response: Response,
     ) -> Result<QueryResults<T>, PrestinoError> {
         let status = response.status();
-        if status != reqwest::StatusCode::OK {
+        if status != StatusCode::OK {
             let message = response.text().await?;
             return Err(PrestinoError::from_status_code(status.as_u16(), message));
         }
         self.headers
             .update_from_response_headers(response.headers())?;
-        // TODO: Make better error messages on json deser.  In particular, if there's a type error,
-        // can we print out the row that causes the error?
+        if let Some(session_headers) = &self.session_headers {
+            session_headers
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .update_from_response_headers(response.headers())?;
+        }
         Ok(response.json().await?)
     }
 
+    /// Cancel the statement whose next results are at `next_uri`, retrying failures
+    /// as the retry policy allows.
     pub async fn cancel(&mut self, next_uri: &str) -> Result<(), PrestinoError> {
-        let response = self
-            .http_client
-            .delete(next_uri)
-            .headers(self.headers.build()?)
-            .send()
-            .await?;
+        let mut attempt = 1;
+        loop {
+            match self.try_cancel(next_uri).await {
+                Err(err) => self.wait_to_retry(err, attempt).await?,
+                result => return result,
+            }
+            attempt += 1;
+        }
+    }
+
+    async fn try_cancel(&mut self, next_uri: &str) -> Result<(), PrestinoError> {
+        let response = self.send(Method::DELETE, next_uri, None).await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let message = response.text().await?;
+            return Err(PrestinoError::from_status_code(status.as_u16(), message));
+        }
+        Ok(())
+    }
 
-        self.parse_response::<()>(response).await.map(|_| ())
+    /// Wait to retry after `err` failed attempt number `attempt`, or return `err` if
+    /// it shouldn't be retried.
+    async fn wait_to_retry(&self, err: PrestinoError, attempt: u32) -> Result<(), PrestinoError> {
+        let Some(delay) = self.retry_policy.retry_delay(&err, attempt) else {
+            return Err(err);
+        };
+        warn!("Retrying in {delay:?} after attempt {attempt} failed: {err}");
+        runtime::sleep(delay).await;
+        Ok(())
     }
 }
@@ -1,45 +1,63 @@
 use crate::Fork;
 use crate::PrestinoError;
+use crate::ResourceEstimate;
 use log::debug;
 use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
 
 #[derive(Debug, Clone)]
 pub struct Headers {
     fork: Fork,
     headers: HeaderMap,
     session_properties: BTreeMap<String, String>,
+    prepared_statements: BTreeMap<String, String>,
+    client_tags: BTreeSet<String>,
+    resource_estimates: BTreeMap<&'static str, ResourceEstimate>,
+    extra_credentials: ExtraCredentials,
+}
+
+/// Extra credentials by name, whose values are hidden from `Debug`.
+#[derive(Clone, Default)]
+struct ExtraCredentials(BTreeMap<String, String>);
+
+impl fmt::Debug for ExtraCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.0.keys()).finish()
+    }
 }
 
 impl Headers {
-    /// Create a Headers instance for Presto
-    pub fn presto() -> Self {
+    fn new(fork: Fork) -> Self {
         Self {
-            fork: Fork::Presto,
+            fork,
             headers: HeaderMap::new(),
             session_properties: BTreeMap::new(),
+            prepared_statements: BTreeMap::new(),
+            client_tags: BTreeSet::new(),
+            resource_estimates: BTreeMap::new(),
+            extra_credentials: ExtraCredentials::default(),
         }
     }
 
+    /// Create a Headers instance for Presto
+    pub fn presto() -> Self {
+        Self::new(Fork::Presto)
+    }
+
     /// Create a Headers instance for Trino
     pub fn trino() -> Self {
-        Self {
-            fork: Fork::Trino,
-            headers: HeaderMap::new(),
-            session_properties: BTreeMap::new(),
-        }
+        Self::new(Fork::Trino)
     }
 
     /// Create a Headers instance with the same fork as this one
     pub fn new_with_fork(&self) -> Self {
-        Self {
-            fork: self.fork,
-            headers: HeaderMap::new(),
-            session_properties: BTreeMap::new(),
-        }
+        Self::new(self.fork)
     }
 
-    /// Update the values in this Head with values from the other Headers.
+    /// Update the values in this Head with values from the other Headers.  Session
+    /// properties, prepared statements, resource estimates and extra credentials are
+    /// replaced by name, and the other's client tags are added to these.
     ///
     /// This will panic if self and other have different forks.
     pub fn update(&mut self, other: &Headers) {
@@ -50,9 +68,16 @@ impl Headers {
                 self.fork, other.fork
             );
         }
-        self.headers.extend(other.headers.clone().into_iter());
+        self.headers.extend(other.headers.clone());
         self.session_properties
-            .extend(other.session_properties.clone().into_iter());
+            .extend(other.session_properties.clone());
+        self.prepared_statements
+            .extend(other.prepared_statements.clone());
+        self.client_tags.extend(other.client_tags.iter().cloned());
+        self.resource_estimates.extend(&other.resource_estimates);
+        self.extra_credentials
+            .0
+            .extend(other.extra_credentials.0.clone());
     }
 
     fn name_for(&self, name: &str) -> HeaderName {
@@ -160,6 +185,13 @@ impl Headers {
         self
     }
 
+    /// The timezone to be used when running the query, if any.  Note that it is lowercased.
+    pub fn get_timezone(&self) -> Option<&str> {
+        self.headers
+            .get(self.name_for("time-zone"))
+            .and_then(|value| value.to_str().ok())
+    }
+
     /// Sets the language to be used when running the query and formatting results.
     /// The `language` field must only contain visible ASCII characters (32-127);
     /// otherwise this function will panic.
@@ -243,7 +275,39 @@ impl Headers {
         self
     }
 
-    // TODO: prepared-statement
+    /// Adds a prepared statement, which can be run with `EXECUTE {name}`.  Multiple
+    /// invocations will add statements; if there is a previous statement with the same
+    /// name, it will overwrite it.
+    ///
+    /// Unlike other headers, the name and statement are URL-encoded rather than
+    /// lowercased, so they may contain any characters.
+    pub fn set_prepared_statement(&mut self, name: &str, statement: &str) -> &mut Self {
+        self.prepared_statements
+            .insert(name.to_owned(), statement.to_owned());
+        self
+    }
+
+    /// Adds a prepared statement, which can be run with `EXECUTE {name}`.  Multiple
+    /// invocations will add statements; if there is a previous statement with the same
+    /// name, it will overwrite it.
+    ///
+    /// Unlike other headers, the name and statement are URL-encoded rather than
+    /// lowercased, so they may contain any characters.
+    pub fn prepared_statement(mut self, name: &str, statement: &str) -> Self {
+        self.set_prepared_statement(name, statement);
+        self
+    }
+
+    /// The statement prepared with the given name, if any.
+    pub fn get_prepared_statement(&self, name: &str) -> Option<&str> {
+        self.prepared_statements.get(name).map(String::as_str)
+    }
+
+    /// Removes the prepared statement with the given name.
+    pub fn clear_prepared_statement(&mut self, name: &str) -> &mut Self {
+        self.prepared_statements.remove(name);
+        self
+    }
 
     /// Sets the transaction ID to use for query processing.
     /// The `transaction-id` field must only contain visible ASCII characters (32-127);
@@ -264,6 +328,13 @@ impl Headers {
         self
     }
 
+    /// The transaction ID used for query processing, if any.
+    pub fn get_transaction_id(&self) -> Option<&str> {
+        self.headers
+            .get(self.name_for("transaction-id"))
+            .and_then(|value| value.to_str().ok())
+    }
+
     /// Clears the Transaction ID used for query processing.
     pub fn clear_transaction_id(&mut self) -> &mut Self {
         self.headers.remove(self.name_for("transaction-id"));
@@ -284,9 +355,116 @@ impl Headers {
         self
     }
 
-    // TODO: client-tags
-    // TODO: resource-estimate
-    // TODO: extra-credential
+    /// Sets the client tags, which identify the kind of query for resource group
+    /// selection, replacing any already set.  Unlike other headers, tags are not
+    /// lowercased, since resource group selectors match them exactly.  Each tag must
+    /// only contain visible ASCII characters (32-127), and no commas; otherwise this
+    /// function will panic.
+    pub fn set_client_tags(&mut self, tags: &[&str]) -> &mut Self {
+        self.client_tags.clear();
+        for tag in tags {
+            self.add_client_tag(tag);
+        }
+        self
+    }
+
+    /// Supplies the client tags, which identify the kind of query for resource group
+    /// selection, replacing any already set.  Unlike other headers, tags are not
+    /// lowercased, since resource group selectors match them exactly.  Each tag must
+    /// only contain visible ASCII characters (32-127), and no commas; otherwise this
+    /// function will panic.
+    pub fn client_tags(mut self, tags: &[&str]) -> Self {
+        self.set_client_tags(tags);
+        self
+    }
+
+    /// Adds a client tag to those already set.  The `tag` must only contain visible
+    /// ASCII characters (32-127), and no commas; otherwise this function will panic.
+    pub fn add_client_tag(&mut self, tag: &str) -> &mut Self {
+        if let Err(message) = check_visible_ascii("client tag", tag) {
+            panic!("{message}");
+        }
+        assert!(
+            !tag.contains(','),
+            "the client tag '{tag}' contains a comma"
+        );
+        self.client_tags.insert(tag.to_owned());
+        self
+    }
+
+    /// The client tags, in sorted order.
+    pub fn get_client_tags(&self) -> impl Iterator<Item = &str> {
+        self.client_tags.iter().map(String::as_str)
+    }
+
+    pub fn clear_client_tags(&mut self) -> &mut Self {
+        self.client_tags.clear();
+        self
+    }
+
+    /// Sets an estimate of the resources the query will use, to help the server
+    /// select its resource group.  It replaces any previous estimate of the same kind.
+    pub fn set_resource_estimate(&mut self, estimate: ResourceEstimate) -> &mut Self {
+        self.resource_estimates.insert(estimate.name(), estimate);
+        self
+    }
+
+    /// Supplies an estimate of the resources the query will use, to help the server
+    /// select its resource group.  It replaces any previous estimate of the same kind.
+    pub fn resource_estimate(mut self, estimate: ResourceEstimate) -> Self {
+        self.set_resource_estimate(estimate);
+        self
+    }
+
+    /// The resource estimates, in order of name.
+    pub fn get_resource_estimates(&self) -> impl Iterator<Item = ResourceEstimate> + '_ {
+        self.resource_estimates.values().copied()
+    }
+
+    pub fn clear_resource_estimates(&mut self) -> &mut Self {
+        self.resource_estimates.clear();
+        self
+    }
+
+    /// Sets an extra credential, which the server passes on to connectors (e.g. to
+    /// authenticate to the underlying data source).  It replaces any previous
+    /// credential with the same name.  The `name` must only contain visible ASCII
+    /// characters (32-127), and no `=` or `,`, otherwise this function will panic.
+    /// The value is URL-encoded, so it may contain any characters.
+    pub fn set_extra_credential(&mut self, name: &str, value: &str) -> &mut Self {
+        if let Err(message) = check_visible_ascii("extra credential name", name) {
+            panic!("{message}");
+        }
+        assert!(
+            !name.contains(['=', ',']),
+            "the extra credential name '{name}' contains '=' or ','"
+        );
+        self.extra_credentials
+            .0
+            .insert(name.to_owned(), value.to_owned());
+        self
+    }
+
+    /// Supplies an extra credential, which the server passes on to connectors (e.g.
+    /// to authenticate to the underlying data source).  It replaces any previous
+    /// credential with the same name.  The `name` must only contain visible ASCII
+    /// characters (32-127), and no `=` or `,`, otherwise this function will panic.
+    /// The value is URL-encoded, so it may contain any characters.
+    pub fn extra_credential(mut self, name: &str, value: &str) -> Self {
+        self.set_extra_credential(name, value);
+        self
+    }
+
+    /// The extra credential with the given name, if any.
+    pub fn get_extra_credential(&self, name: &str) -> Option<&str> {
+        self.extra_credentials.0.get(name).map(String::as_str)
+    }
+
+    /// Removes the extra credential with the given name.
+    pub fn clear_extra_credential(&mut self, name: &str) -> &mut Self {
+        self.extra_credentials.0.remove(name);
+        self
+    }
 
     pub fn build(&self) -> Result<HeaderMap, PrestinoError> {
         let mut headers = self.headers.clone();
@@ -299,6 +477,36 @@ impl Headers {
         if let Some(session_value) = session_value_opt {
             headers.insert(self.name_for("session"), session_value.parse()?);
         }
+        let prepared_value_opt: Option<String> = self
+            .prepared_statements
+            .iter()
+            .map(|(name, statement)| {
+                form_urlencoded::Serializer::new(String::new())
+                    .append_pair(name, statement)
+                    .finish()
+            })
+            .reduce(|base, next| base + "," + &next);
+        if let Some(prepared_value) = prepared_value_opt {
+            headers.insert(self.name_for("prepared-statement"), prepared_value.parse()?);
+        }
+        if !self.client_tags.is_empty() {
+            let tags = Vec::from_iter(self.client_tags.iter().map(String::as_str)).join(",");
+            headers.insert(self.name_for("client-tags"), tags.parse()?);
+        }
+        if !self.resource_estimates.is_empty() {
+            let estimates =
+                Vec::from_iter(self.resource_estimates.values().map(ToString::to_string));
+            headers.insert(
+                self.name_for("resource-estimate"),
+                estimates.join(",").parse()?,
+            );
+        }
+        for (name, value) in &self.extra_credentials.0 {
+            let value: String = form_urlencoded::byte_serialize(value.as_bytes()).collect();
+            let mut value: HeaderValue = format!("{name}={value}").parse()?;
+            value.set_sensitive(true);
+            headers.append(self.name_for("extra-credential"), value);
+        }
 
         Ok(headers)
     }
@@ -355,8 +563,21 @@ impl Headers {
                 debug!("Clearing Transaction Id");
                 self.clear_transaction_id();
             }
-            // TODO: added-prepare
-            // TODO: deallocated-prepare
+            Some("added-prepare") => {
+                let Some((name, statement)) = form_urlencoded::parse(value.as_bytes()).next()
+                else {
+                    return Err(PrestinoError::HeaderParseError);
+                };
+                debug!("Adding prepared statement {name}");
+                self.set_prepared_statement(&name, &statement);
+            }
+            Some("deallocated-prepare") => {
+                let Some((name, _)) = form_urlencoded::parse(value.as_bytes()).next() else {
+                    return Err(PrestinoError::HeaderParseError);
+                };
+                debug!("Deallocating prepared statement {name}");
+                self.clear_prepared_statement(&name);
+            }
             Some(_) => debug!("Unprocessed response header: {name:?}"),
             None => (),
         }
@@ -364,9 +585,22 @@ impl Headers {
     }
 }
 
+/// Check that a value for a header only contains visible ASCII characters, so that
+/// setting it won't panic, naming the `field` in the error if it doesn't.
+pub(crate) fn check_visible_ascii(field: &str, value: &str) -> Result<(), String> {
+    if value.chars().all(|c| c.is_ascii() && !c.is_ascii_control()) {
+        Ok(())
+    } else {
+        Err(format!(
+            "the {field} '{value}' must only contain visible ASCII characters"
+        ))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::time::Duration;
 
     fn get_value(header_map: &HeaderMap, name: &str) -> Option<String> {
         let header_value = header_map.get(name);
@@ -493,6 +727,43 @@ mod tests {
         assert!(!header_map.contains_key("X-Trino-Transaction-Id"));
     }
 
+    #[test]
+    fn test_prepared_statements() {
+        let mut request_headers = Headers::trino().prepared_statement("q1", "SELECT 1");
+        let mut response_header_map = HeaderMap::new();
+        response_header_map.insert(
+            "X-Trino-Added-Prepare",
+            "my_query=SELECT+*+FROM+t+WHERE+name+%3D+%27Bob%2C+Jr%27"
+                .parse()
+                .unwrap(),
+        );
+        request_headers
+            .update_from_response_headers(&response_header_map)
+            .unwrap();
+        assert_eq!(
+            request_headers.get_prepared_statement("my_query"),
+            Some("SELECT * FROM t WHERE name = 'Bob, Jr'")
+        );
+
+        let header_map = request_headers.build().unwrap();
+        assert_eq!(
+            get_value(&header_map, "x-trino-prepared-statement"),
+            Some("my_query=SELECT+*+FROM+t+WHERE+name+%3D+%27Bob%2C+Jr%27,q1=SELECT+1".to_string())
+        );
+
+        response_header_map.clear();
+        response_header_map.insert("X-Trino-Deallocated-Prepare", "q1".parse().unwrap());
+        request_headers
+            .update_from_response_headers(&response_header_map)
+            .unwrap();
+        assert_eq!(request_headers.get_prepared_statement("q1"), None);
+        let header_map = request_headers.build().unwrap();
+        assert_eq!(
+            get_value(&header_map, "x-trino-prepared-statement"),
+            Some("my_query=SELECT+*+FROM+t+WHERE+name+%3D+%27Bob%2C+Jr%27".to_string())
+        );
+    }
+
     #[test]
     fn test_merge() {
         let mut base_headers = Headers::trino()
@@ -526,4 +797,93 @@ mod tests {
             Some("a=1,b=4,c=3".to_string())
         );
     }
+
+    #[test]
+    fn test_client_tags() {
+        let mut headers = Headers::trino().client_tags(&["Nightly", "etl"]);
+        headers.add_client_tag("etl").add_client_tag("BI");
+        let header_map = headers.build().unwrap();
+        assert_eq!(
+            get_value(&header_map, "x-trino-client-tags"),
+            Some("BI,Nightly,etl".to_string())
+        );
+
+        headers.clear_client_tags();
+        assert!(!headers.build().unwrap().contains_key("x-trino-client-tags"));
+    }
+
+    #[test]
+    #[should_panic(expected = "contains a comma")]
+    fn test_client_tag_with_comma() {
+        Headers::trino().add_client_tag("a,b");
+    }
+
+    #[test]
+    fn test_resource_estimates() {
+        let headers = Headers::presto()
+            .resource_estimate(ResourceEstimate::PeakMemory(8 << 30))
+            .resource_estimate(ResourceEstimate::ExecutionTime(Duration::from_secs(60)))
+            .resource_estimate(ResourceEstimate::CpuTime(Duration::from_millis(1500)))
+            .resource_estimate(ResourceEstimate::ExecutionTime(Duration::from_secs(5400)));
+        let header_map = headers.build().unwrap();
+        assert_eq!(
+            get_value(&header_map, "x-presto-resource-estimate"),
+            Some("CPU_TIME=1500ms,EXECUTION_TIME=90m,PEAK_MEMORY=8GB".to_string())
+        );
+    }
+
+    #[test]
+    fn test_extra_credentials() {
+        let headers = Headers::trino()
+            .extra_credential("s3.secret", "a+b/c d=")
+            .extra_credential("db.token", "xyz");
+        let header_map = headers.build().unwrap();
+        let values: Vec<_> = header_map
+            .get_all("x-trino-extra-credential")
+            .iter()
+            .map(|value| {
+                assert!(value.is_sensitive());
+                value.to_str().unwrap()
+            })
+            .collect();
+        assert_eq!(values, vec!["db.token=xyz", "s3.secret=a%2Bb%2Fc+d%3D"]);
+        assert_eq!(headers.get_extra_credential("s3.secret"), Some("a+b/c d="));
+
+        let debug = format!("{headers:?}");
+        assert!(
+            debug.contains("s3.secret") && !debug.contains("xyz"),
+            "{debug}"
+        );
+    }
+
+    #[test]
+    fn test_merge_tags_estimates_and_credentials() {
+        let mut base_headers = Headers::trino()
+            .client_tags(&["etl"])
+            .resource_estimate(ResourceEstimate::PeakMemory(1 << 30))
+            .resource_estimate(ResourceEstimate::CpuTime(Duration::from_secs(60)))
+            .extra_credential("a", "1");
+        let new_headers = Headers::trino()
+            .client_tags(&["adhoc"])
+            .resource_estimate(ResourceEstimate::PeakMemory(2 << 30))
+            .extra_credential("a", "2")
+            .extra_credential("b", "3");
+
+        base_headers.update(&new_headers);
+        let header_map = base_headers.build().unwrap();
+        assert_eq!(
+            get_value(&header_map, "x-trino-client-tags"),
+            Some("adhoc,etl".to_string())
+        );
+        assert_eq!(
+            get_value(&header_map, "x-trino-resource-estimate"),
+            Some("CPU_TIME=1m,PEAK_MEMORY=2GB".to_string())
+        );
+        let credentials: Vec<_> = header_map
+            .get_all("x-trino-extra-credential")
+            .iter()
+            .map(|value| value.to_str().unwrap())
+            .collect();
+        assert_eq!(credentials, vec!["a=2", "b=3"]);
+    }
 }
@@ -1,15 +1,42 @@
+mod authentication;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+mod cli_config;
 mod client_connection;
+mod connection_string;
 mod headers;
+mod poll_backoff;
+mod prepared_statement;
 mod prestino_client;
+mod prestino_client_builder;
 mod prestino_error;
+mod resource_estimate;
 pub mod results;
+mod retry_policy;
+mod row_decoder;
+mod runtime;
+mod session;
+mod sql_literal;
 mod statement_executor;
+mod transaction;
+mod value_deserializer;
 
+pub use authentication::{Authentication, ExternalAuthentication};
+pub use cli_config::CliConfig;
+pub use connection_string::ConnectionString;
 pub use headers::Headers;
+pub use poll_backoff::PollBackoff;
+pub use prepared_statement::PreparedStatement;
 pub use prestino_client::PrestinoClient;
-pub use prestino_error::PrestinoError;
+pub use prestino_client_builder::PrestinoClientBuilder;
+pub use prestino_error::{PrestinoError, RowDecodeError};
+pub use resource_estimate::ResourceEstimate;
 pub use results::QueryStats;
+pub use retry_policy::RetryPolicy;
+pub use session::Session;
+pub use sql_literal::{format_sql, ToSqlLiteral};
 pub use statement_executor::StatementExecutor;
+pub use transaction::{IsolationLevel, Transaction};
 
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum Fork {
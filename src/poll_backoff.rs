@@ -0,0 +1,132 @@
+use std::time::Duration;
+
+/// How long to wait before polling a statement for more results, after a poll that
+/// returned no rows.  A poll that returns rows is followed immediately by the next.
+///
+/// The wait starts at `initial` after the first empty poll, and is multiplied by
+/// `multiplier` after each successive empty poll, up to `max`.  While the statement
+/// is queued, the wait is at least the `queued` wait, if one is set, since a queued
+/// statement can wait much longer for a slot than a running one for its next rows.
+/// The queued wait doesn't change the backoff itself, so once the statement is
+/// running, the waits are the ones it would have had without it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PollBackoff {
+    initial: Duration,
+    max: Duration,
+    multiplier: f64,
+    queued: Option<Duration>,
+}
+
+impl PollBackoff {
+    /// Always wait `delay` after an empty poll.
+    pub fn fixed(delay: Duration) -> Self {
+        Self {
+            initial: delay,
+            max: delay,
+            multiplier: 1.0,
+            queued: None,
+        }
+    }
+
+    /// Wait `initial` after the first empty poll, doubling after each successive
+    /// empty poll up to `max`.
+    pub fn exponential(initial: Duration, max: Duration) -> Self {
+        Self {
+            initial,
+            max: max.max(initial),
+            multiplier: 2.0,
+            queued: None,
+        }
+    }
+
+    /// How much longer to wait after each successive empty poll.  At least 1.
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier.max(1.0);
+        self
+    }
+
+    /// The least to wait after a poll that found the statement still queued.
+    pub fn queued(mut self, delay: Duration) -> Self {
+        self.queued = Some(delay);
+        self
+    }
+
+    /// The backoff delay after an empty poll, given the delay after the previous poll
+    /// (which is zero if it returned rows).
+    pub(crate) fn next_delay(&self, previous: Duration) -> Duration {
+        if previous.is_zero() {
+            self.initial
+        } else {
+            previous.mul_f64(self.multiplier).min(self.max)
+        }
+    }
+
+    /// How long to wait before the next poll, given the backoff delay and whether the
+    /// statement is queued.
+    pub(crate) fn wait(&self, delay: Duration, queued: bool) -> Duration {
+        match self.queued {
+            Some(queued_delay) if queued && !delay.is_zero() => delay.max(queued_delay),
+            _ => delay,
+        }
+    }
+}
+
+impl Default for PollBackoff {
+    /// Wait 100ms after the first empty poll, doubling up to 1s.
+    fn default() -> Self {
+        Self::exponential(Duration::from_millis(100), Duration::from_secs(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The waits after successive empty polls, which found the statement queued or not.
+    fn delays(backoff: PollBackoff, queued: &[bool]) -> Vec<u64> {
+        let mut delay = Duration::ZERO;
+        queued
+            .iter()
+            .map(|&queued| {
+                delay = backoff.next_delay(delay);
+                backoff.wait(delay, queued).as_millis() as u64
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_exponential() {
+        let backoff =
+            PollBackoff::exponential(Duration::from_millis(50), Duration::from_millis(300));
+        assert_eq!(delays(backoff, &[false; 5]), vec![50, 100, 200, 300, 300]);
+        let backoff = backoff.multiplier(3.0);
+        assert_eq!(delays(backoff, &[false; 3]), vec![50, 150, 300]);
+    }
+
+    #[test]
+    fn test_fixed() {
+        let backoff = PollBackoff::fixed(Duration::from_millis(100));
+        assert_eq!(delays(backoff, &[false; 3]), vec![100, 100, 100]);
+    }
+
+    #[test]
+    fn test_queued() {
+        let backoff =
+            PollBackoff::exponential(Duration::from_millis(50), Duration::from_millis(800))
+                .queued(Duration::from_millis(250));
+        assert_eq!(
+            delays(backoff, &[true, true, true, true, true]),
+            vec![250, 250, 250, 400, 800]
+        );
+    }
+
+    #[test]
+    fn test_queued_to_running() {
+        let backoff = PollBackoff::exponential(Duration::from_millis(50), Duration::from_secs(2))
+            .queued(Duration::from_secs(1));
+        assert_eq!(
+            delays(backoff, &[true, true, false, false, false]),
+            vec![1000, 1000, 200, 400, 800]
+        );
+    }
+}
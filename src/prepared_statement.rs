@@ -0,0 +1,111 @@
+use crate::{PrestinoError, Session, StatementExecutor, ToSqlLiteral};
+use log::debug;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+/// A statement prepared in a Session with `Session::prepare`, which can be executed
+/// repeatedly with different parameters.  The parameters are bound as SQL literals
+/// with `EXECUTE ... USING`, so values never need to be formatted into the statement.
+///
+/// Prepared statements are kept in the Session's headers, so dropping a PreparedStatement
+/// removes it from the Session.  `deallocate()` does the same through the server.
+#[derive(Debug)]
+pub struct PreparedStatement<'a> {
+    session: &'a Session,
+    name: String,
+    deallocated: bool,
+}
+
+impl<'a> PreparedStatement<'a> {
+    pub(crate) async fn prepare(
+        session: &'a Session,
+        name: &str,
+        statement: &str,
+    ) -> Result<PreparedStatement<'a>, PrestinoError> {
+        if !is_identifier(name) {
+            return Err(PrestinoError::StatementNameError(name.to_owned()));
+        }
+        session
+            .execute_collect::<Value>(format!("PREPARE {name} FROM {statement}"))
+            .await?;
+        debug!("Prepared statement {name}");
+        Ok(PreparedStatement {
+            session,
+            name: name.to_owned(),
+            deallocated: false,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The statement as prepared by the server.
+    pub fn statement(&self) -> Option<String> {
+        self.session
+            .lock_headers()
+            .get_prepared_statement(&self.name)
+            .map(str::to_owned)
+    }
+
+    /// The `EXECUTE` statement for this prepared statement with the given parameters.
+    fn execute_statement(&self, params: &[&dyn ToSqlLiteral]) -> String {
+        if params.is_empty() {
+            return format!("EXECUTE {}", self.name);
+        }
+        let literals: Vec<String> = params.iter().map(|p| p.to_sql_literal()).collect();
+        format!("EXECUTE {} USING {}", self.name, literals.join(", "))
+    }
+
+    /// Begin execution of the statement with the given parameters, returning a
+    /// StatementExecutor to continue execution.
+    pub async fn execute<T: DeserializeOwned>(
+        &self,
+        params: &[&dyn ToSqlLiteral],
+    ) -> Result<StatementExecutor<T>, PrestinoError> {
+        self.session.execute(self.execute_statement(params)).await
+    }
+
+    /// A convenience function to retrieve all the rows for the statement with the
+    /// given parameters into a single Vec.
+    pub async fn execute_collect<T: DeserializeOwned>(
+        &self,
+        params: &[&dyn ToSqlLiteral],
+    ) -> Result<Vec<T>, PrestinoError> {
+        self.session
+            .execute_collect(self.execute_statement(params))
+            .await
+    }
+
+    /// Deallocate the statement with `DEALLOCATE PREPARE`.
+    pub async fn deallocate(mut self) -> Result<(), PrestinoError> {
+        self.deallocated = true;
+        let result = self
+            .session
+            .execute_collect::<Value>(format!("DEALLOCATE PREPARE {}", self.name))
+            .await;
+        self.session
+            .lock_headers()
+            .clear_prepared_statement(&self.name);
+        result.map(|_| ())
+    }
+}
+
+/// Whether the name is a plain identifier, `[A-Za-z_][A-Za-z0-9_]*`, and so can be
+/// put into the `PREPARE`, `EXECUTE` and `DEALLOCATE` statements as is.
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+impl<'a> Drop for PreparedStatement<'a> {
+    fn drop(&mut self) {
+        if !self.deallocated {
+            debug!("Removing dropped prepared statement {}", self.name);
+            self.session
+                .lock_headers()
+                .clear_prepared_statement(&self.name);
+        }
+    }
+}
@@ -1,16 +1,26 @@
 use crate::client_connection::ClientConnection;
 use crate::headers::Headers;
-use crate::{PrestinoError, StatementExecutor};
-use futures::pin_mut;
-use futures::TryStreamExt;
+use crate::runtime;
+use crate::statement_executor::Timeouts;
+use crate::{
+    Authentication, ConnectionString, PollBackoff, PrestinoClientBuilder, PrestinoError,
+    RetryPolicy, Session, StatementExecutor,
+};
 use reqwest::Client;
 use serde::de::DeserializeOwned;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 #[derive(Debug, Clone)]
 pub struct PrestinoClient {
     base_url: String,
     headers: Headers,
     http_client: Client,
+    timeouts: Timeouts,
+    retry_policy: RetryPolicy,
+    poll_backoff: PollBackoff,
+    authentication: Option<Authentication>,
+    allow_insecure_authentication: bool,
 }
 
 impl PrestinoClient {
@@ -26,13 +36,37 @@ impl PrestinoClient {
 
     /// Create a client with the headers set.  The headers fork will determine the client's fork.
     pub fn with_headers(base_url: impl Into<String>, headers: Headers) -> Self {
+        Self::from_parts(base_url.into(), headers, Client::new())
+    }
+
+    /// Start building a Trino client whose connection needs configuring, e.g. for TLS.
+    pub fn builder(base_url: impl Into<String>) -> PrestinoClientBuilder {
+        PrestinoClientBuilder::new(base_url, Headers::trino())
+    }
+
+    pub(crate) fn from_parts(base_url: String, headers: Headers, http_client: Client) -> Self {
         Self {
-            base_url: base_url.into(),
+            base_url,
             headers,
-            http_client: Client::new(),
+            http_client,
+            timeouts: Timeouts::default(),
+            retry_policy: RetryPolicy::default(),
+            poll_backoff: PollBackoff::default(),
+            authentication: None,
+            allow_insecure_authentication: false,
         }
     }
 
+    /// Create a client configured by a connection string, like
+    /// `trino://user@host:port/catalog/schema?source=etl`.  See `ConnectionString`.
+    pub fn from_connection_string(connection_string: &str) -> Result<Self, PrestinoError> {
+        connection_string.parse::<ConnectionString>()?.client()
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
     pub fn headers(&self) -> &Headers {
         &self.headers
     }
@@ -47,6 +81,56 @@ impl PrestinoClient {
         self
     }
 
+    /// Authenticate to the server, for each request of each statement.
+    pub fn authentication(mut self, authentication: Authentication) -> Self {
+        self.authentication = Some(authentication);
+        self
+    }
+
+    /// Allow credentials to be sent over plain HTTP, instead of failing with
+    /// `PrestinoError::InsecureAuthenticationError`.  Only for testing, or a server
+    /// reached over a trusted network.
+    pub fn allow_insecure_authentication(mut self, allow: bool) -> Self {
+        self.allow_insecure_authentication = allow;
+        self
+    }
+
+    /// Time out each statement that hasn't finished this long after it was submitted.
+    /// The statement is cancelled, and its results return `PrestinoError::Timeout`.
+    /// Each statement's timeout can also be set on its `StatementExecutor`.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeouts.total = Some(timeout);
+        self
+    }
+
+    /// Time out each statement that goes this long without making progress (that is,
+    /// without returning rows or advancing its state, splits or processed rows).
+    pub fn progress_timeout(mut self, timeout: Duration) -> Self {
+        self.timeouts.no_progress = Some(timeout);
+        self
+    }
+
+    /// How to retry failed requests for the next results of a statement, and to
+    /// cancel one.  By default, transient failures are retried a few times.
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// How long to wait between polls for the results of a statement that has none
+    /// ready yet.
+    pub fn poll_backoff(mut self, poll_backoff: PollBackoff) -> Self {
+        self.poll_backoff = poll_backoff;
+        self
+    }
+
+    /// Create a Session, which starts with this client's headers and keeps the
+    /// state (catalog, schema, session properties, role, transaction) that the
+    /// server sets while running its statements.
+    pub fn session(&self) -> Session {
+        Session::new(self.clone())
+    }
+
     /// Begin execution of a statement, returning a StatementExecutor to continue execution.
     pub async fn execute<T: DeserializeOwned>(
         &self,
@@ -64,19 +148,40 @@ impl PrestinoClient {
     ) -> Result<StatementExecutor<T>, PrestinoError> {
         let mut connection_headers = self.headers.clone();
         connection_headers.update(headers);
+        self.start_statement(statement, connection_headers, None)
+            .await
+    }
 
+    /// Post the statement with exactly the given headers.  If `session_headers` is
+    /// supplied, it will be updated with any state the server sets while the statement runs.
+    pub(crate) async fn start_statement<T: DeserializeOwned>(
+        &self,
+        statement: impl Into<String>,
+        headers: Headers,
+        session_headers: Option<Arc<Mutex<Headers>>>,
+    ) -> Result<StatementExecutor<T>, PrestinoError> {
+        let started = Instant::now();
         let mut connection = ClientConnection {
-            headers: connection_headers,
+            headers,
             http_client: self.http_client.clone(),
+            session_headers,
+            retry_policy: self.retry_policy.clone(),
+            authentication: self.authentication.clone(),
+            allow_insecure_authentication: self.allow_insecure_authentication,
         };
 
-        let results = connection.post_statement(&self.base_url, statement).await?;
+        let deadline = self.timeouts.total.map(|timeout| started + timeout);
+        let response = connection.post_statement(&self.base_url, statement);
+        let Some(results) = runtime::run_until(deadline, response).await else {
+            return Err(PrestinoError::Timeout(String::new(), Box::default()));
+        };
+        let results = results?;
 
-        Ok(StatementExecutor::new(
-            results.id.clone(),
-            connection,
-            results,
-        ))
+        Ok(
+            StatementExecutor::new(results.id.clone(), connection, results)
+                .with_timeouts(self.timeouts, started)
+                .with_poll_backoff(self.poll_backoff),
+        )
     }
 
     /// A convenience function to retrieve all the rows for the statement into a single Vec.
@@ -95,14 +200,9 @@ impl PrestinoClient {
         statement: impl Into<String>,
         headers: &Headers,
     ) -> Result<Vec<T>, PrestinoError> {
-        let mut rows: Vec<T> = Vec::new();
-        let executor = self.execute_with_headers::<T>(statement, headers).await?;
-        let stream = executor.batches();
-        pin_mut!(stream);
-        while let Some(batch) = stream.try_next().await? {
-            rows.extend(batch);
-        }
-
-        Ok(rows)
+        self.execute_with_headers::<T>(statement, headers)
+            .await?
+            .collect()
+            .await
     }
 }
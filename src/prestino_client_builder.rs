@@ -0,0 +1,355 @@
+use crate::{Headers, PrestinoClient, PrestinoError};
+#[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+use reqwest::{Certificate, Identity};
+use reqwest::{Client, ClientBuilder, Proxy, Url};
+#[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+use std::fs;
+#[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Builds a PrestinoClient whose connection to the server needs configuring, e.g.
+/// with timeouts, a proxy, or to trust an internal certificate authority.  The base
+/// URL is checked when the client is built, rather than when the first statement is
+/// sent.
+///
+/// The TLS implementation is chosen by cargo feature: `native-tls` (the default) or
+/// `rustls-tls`.  Some options are only available with one of them, and none
+/// without either.
+#[derive(Debug, Clone)]
+pub struct PrestinoClientBuilder {
+    base_url: String,
+    headers: Headers,
+    http_client: Option<Client>,
+    connect_timeout: Option<Duration>,
+    request_timeout: Option<Duration>,
+    tcp_keepalive: Option<Duration>,
+    pool_idle_timeout: Option<Duration>,
+    pool_max_idle_per_host: Option<usize>,
+    user_agent: Option<String>,
+    proxies: Vec<Proxy>,
+    no_proxy: bool,
+    #[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+    root_certificates: Vec<PathBuf>,
+    #[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+    identity: Option<IdentityFiles>,
+    #[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+    accept_invalid_certs: bool,
+    #[cfg(feature = "native-tls")]
+    accept_invalid_hostnames: bool,
+}
+
+/// The files of the client's certificate and private key, for mutual TLS.
+#[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+#[derive(Debug, Clone)]
+enum IdentityFiles {
+    #[cfg(feature = "native-tls")]
+    Pkcs12 {
+        path: PathBuf,
+        password: String,
+    },
+    Pem {
+        certificate: PathBuf,
+        key: PathBuf,
+    },
+}
+
+impl PrestinoClientBuilder {
+    /// Start building a client for the server at `base_url`, with the given headers.
+    /// The headers fork will determine the client's fork.
+    pub fn new(base_url: impl Into<String>, headers: Headers) -> Self {
+        Self {
+            base_url: base_url.into(),
+            headers,
+            http_client: None,
+            connect_timeout: None,
+            request_timeout: None,
+            tcp_keepalive: None,
+            pool_idle_timeout: None,
+            pool_max_idle_per_host: None,
+            user_agent: None,
+            proxies: Vec::new(),
+            no_proxy: false,
+            #[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+            root_certificates: Vec::new(),
+            #[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+            identity: None,
+            #[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+            accept_invalid_certs: false,
+            #[cfg(feature = "native-tls")]
+            accept_invalid_hostnames: false,
+        }
+    }
+
+    pub fn headers(mut self, headers: Headers) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    /// Convenience function to set the user header.
+    pub fn user(mut self, user: &str) -> Self {
+        self.headers.set_user(user);
+        self
+    }
+
+    /// Use this HTTP client, e.g. to share its connection pool with the rest of an
+    /// application.  The builder's other connection settings (timeouts, keep-alive,
+    /// pool, user agent, proxies and TLS) are then not applied, since they are the
+    /// client's own.
+    pub fn http_client(mut self, http_client: Client) -> Self {
+        self.http_client = Some(http_client);
+        self
+    }
+
+    /// How long to wait to connect to the server.  By default there is no limit.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// How long to wait for each request to the server, from connecting until the
+    /// response is read.  Polls for results are short, so this is in effect a read
+    /// timeout; a statement can still run for longer (see `PrestinoClient::timeout`).
+    /// By default there is no limit.
+    pub fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = Some(timeout);
+        self
+    }
+
+    /// Send TCP keep-alive probes on idle connections at this interval.
+    pub fn tcp_keepalive(mut self, interval: Duration) -> Self {
+        self.tcp_keepalive = Some(interval);
+        self
+    }
+
+    /// How long to keep an idle connection open for reuse.  The default is 90s.
+    pub fn pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// The most idle connections to keep open for reuse.  By default there is no
+    /// limit.
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Send requests through this proxy (when it applies to them).  By default, the
+    /// proxies in the `HTTP_PROXY`, `HTTPS_PROXY` and `NO_PROXY` environment variables
+    /// are used.
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxies.push(proxy);
+        self
+    }
+
+    /// Don't use any proxy, including from the environment.
+    pub fn no_proxy(mut self) -> Self {
+        self.no_proxy = true;
+        self
+    }
+
+    /// Build the client, checking the base URL and reading any certificate files.
+    pub fn build(self) -> Result<PrestinoClient, PrestinoError> {
+        let base_url = validate_base_url(&self.base_url)?;
+        let http_client = match &self.http_client {
+            Some(http_client) => http_client.clone(),
+            None => self.configure_tls(self.configure_connection())?.build()?,
+        };
+        Ok(PrestinoClient::from_parts(
+            base_url,
+            self.headers,
+            http_client,
+        ))
+    }
+
+    fn configure_connection(&self) -> ClientBuilder {
+        let mut builder = Client::builder();
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(timeout) = self.request_timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(interval) = self.tcp_keepalive {
+            builder = builder.tcp_keepalive(interval);
+        }
+        if let Some(timeout) = self.pool_idle_timeout {
+            builder = builder.pool_idle_timeout(timeout);
+        }
+        if let Some(max) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        if let Some(user_agent) = &self.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+        if self.no_proxy {
+            builder = builder.no_proxy();
+        }
+        for proxy in &self.proxies {
+            builder = builder.proxy(proxy.clone());
+        }
+        builder
+    }
+
+    #[cfg(not(any(feature = "native-tls", feature = "rustls-tls")))]
+    fn configure_tls(&self, builder: ClientBuilder) -> Result<ClientBuilder, PrestinoError> {
+        Ok(builder)
+    }
+}
+
+#[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+impl PrestinoClientBuilder {
+    /// Trust the certificates in a PEM file (which may hold several), in addition
+    /// to the system's trusted certificates.
+    pub fn root_certificate_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.root_certificates.push(path.into());
+        self
+    }
+
+    /// Present the client certificate and key in a PKCS#12 file to the server.
+    #[cfg(feature = "native-tls")]
+    pub fn identity_pkcs12_file(mut self, path: impl Into<PathBuf>, password: &str) -> Self {
+        self.identity = Some(IdentityFiles::Pkcs12 {
+            path: path.into(),
+            password: password.to_owned(),
+        });
+        self
+    }
+
+    /// Present the client certificate (chain) and PKCS#8 private key in the PEM files
+    /// to the server.
+    pub fn identity_pem_files(
+        mut self,
+        certificate: impl Into<PathBuf>,
+        key: impl Into<PathBuf>,
+    ) -> Self {
+        self.identity = Some(IdentityFiles::Pem {
+            certificate: certificate.into(),
+            key: key.into(),
+        });
+        self
+    }
+
+    /// Accept any server certificate, even an expired or self-signed one, or one for
+    /// another host.  This makes the connection open to interception, so it is only
+    /// for development clusters.
+    pub fn danger_accept_invalid_certs(mut self, accept: bool) -> Self {
+        self.accept_invalid_certs = accept;
+        self
+    }
+
+    /// Accept a valid server certificate for another host.  This makes the
+    /// connection open to interception, so it is only for development clusters.
+    #[cfg(feature = "native-tls")]
+    pub fn danger_accept_invalid_hostnames(mut self, accept: bool) -> Self {
+        self.accept_invalid_hostnames = accept;
+        self
+    }
+
+    fn configure_tls(&self, mut builder: ClientBuilder) -> Result<ClientBuilder, PrestinoError> {
+        for path in &self.root_certificates {
+            let certificates = Certificate::from_pem_bundle(&read_file(path)?)
+                .map_err(|err| tls_error("root certificate", path, err))?;
+            for certificate in certificates {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+        if let Some(identity) = &self.identity {
+            builder = builder.identity(identity.load()?);
+        }
+        builder = builder.danger_accept_invalid_certs(self.accept_invalid_certs);
+        #[cfg(feature = "native-tls")]
+        {
+            builder = builder.danger_accept_invalid_hostnames(self.accept_invalid_hostnames);
+        }
+        Ok(builder)
+    }
+}
+
+#[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+impl IdentityFiles {
+    fn load(&self) -> Result<Identity, PrestinoError> {
+        match self {
+            #[cfg(feature = "native-tls")]
+            Self::Pkcs12 { path, password } => {
+                Identity::from_pkcs12_der(&read_file(path)?, password)
+                    .map_err(|err| tls_error("PKCS#12 identity", path, err))
+            }
+            Self::Pem { certificate, key } => {
+                let certificate_pem = read_file(certificate)?;
+                let key_pem = read_file(key)?;
+                #[cfg(feature = "native-tls")]
+                let identity = Identity::from_pkcs8_pem(&certificate_pem, &key_pem);
+                #[cfg(not(feature = "native-tls"))]
+                let identity = Identity::from_pem(&[key_pem, certificate_pem].concat());
+                identity.map_err(|err| tls_error("PEM identity", certificate, err))
+            }
+        }
+    }
+}
+
+#[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+fn read_file(path: &Path) -> Result<Vec<u8>, PrestinoError> {
+    fs::read(path).map_err(|err| {
+        PrestinoError::TlsConfigError(format!("could not read {}: {err}", path.display()))
+    })
+}
+
+#[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+fn tls_error(kind: &str, path: &Path, err: reqwest::Error) -> PrestinoError {
+    PrestinoError::TlsConfigError(format!("invalid {kind} in {}: {err}", path.display()))
+}
+
+/// Check that the base URL is an http(s) URL that statement paths can be appended
+/// to, returning it without any trailing slash.
+fn validate_base_url(base_url: &str) -> Result<String, PrestinoError> {
+    let invalid = |reason: String| PrestinoError::BaseUrlError(base_url.to_owned(), reason);
+    let url = Url::parse(base_url).map_err(|err| invalid(err.to_string()))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(invalid("the scheme must be http or https".to_owned()));
+    }
+    if url.host_str().is_none() {
+        return Err(invalid("there is no host".to_owned()));
+    }
+    if url.query().is_some() || url.fragment().is_some() {
+        return Err(invalid("it must not have a query or fragment".to_owned()));
+    }
+    Ok(base_url.trim_end_matches('/').to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_base_url() {
+        assert_eq!(
+            validate_base_url("http://localhost:8080").unwrap(),
+            "http://localhost:8080"
+        );
+        assert_eq!(
+            validate_base_url("https://trino.example.com/gateway/").unwrap(),
+            "https://trino.example.com/gateway"
+        );
+        for base_url in [
+            "localhost:8080",
+            "trino.example.com",
+            "ftp://trino.example.com",
+            "http://",
+            "http://localhost:8080?catalog=hive",
+            "http://localhost:8080#top",
+        ] {
+            let err = validate_base_url(base_url).unwrap_err();
+            assert!(
+                matches!(&err, PrestinoError::BaseUrlError(url, _) if url == base_url),
+                "{base_url}: {err:?}"
+            );
+        }
+    }
+}
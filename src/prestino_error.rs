@@ -1,3 +1,8 @@
+use crate::results::{Column, QueryStats};
+use serde_json::Value;
+use std::error::Error as StdError;
+use std::fmt;
+
 #[derive(thiserror::Error, Debug)]
 pub enum PrestinoError {
     #[error("Http error")]
@@ -8,10 +13,39 @@ pub enum PrestinoError {
     JsonParseError(#[from] serde_json::Error),
     #[error("Error in query")]
     QueryError(#[from] crate::results::QueryError),
+    #[error("Result columns do not match the row type: {0}")]
+    ColumnMismatchError(String),
+    #[error("Could not decode value: {0}")]
+    ValueDecodeError(String),
     #[error("Query {0} already finished")]
     QueryFinishedError(String),
     #[error("Header names and values must only contain visible ASCII characters")]
     HeaderParseError,
+    #[error("Transaction error: {0}")]
+    TransactionError(String),
+    #[error("Invalid prepared statement name '{0}': it must be an identifier")]
+    StatementNameError(String),
+    #[error("No value supplied for parameter :{0}")]
+    MissingParameterError(String),
+    #[error("{0}")]
+    RowDecodeError(Box<RowDecodeError>),
+    #[error("Invalid client configuration: {0}")]
+    ConfigError(String),
+    #[error("Invalid connection string: {0}")]
+    ConnectionStringError(String),
+    #[error("Invalid base URL {0}: {1}")]
+    BaseUrlError(String, String),
+    #[error("TLS configuration error: {0}")]
+    TlsConfigError(String),
+    #[error("Refusing to send credentials over insecure HTTP to {0}")]
+    InsecureAuthenticationError(String),
+    #[error("External authentication failed: {0}")]
+    ExternalAuthenticationError(String),
+    /// The statement's id and last stats.  If the statement timed out before the
+    /// server answered its `POST`, there is no id yet, so it is empty, and the stats
+    /// are the defaults.
+    #[error("Query {0} timed out and was cancelled")]
+    Timeout(String, Box<QueryStats>),
 }
 
 impl PrestinoError {
@@ -20,6 +54,52 @@ impl PrestinoError {
     }
 }
 
+impl From<RowDecodeError> for PrestinoError {
+    fn from(err: RowDecodeError) -> Self {
+        PrestinoError::RowDecodeError(Box::new(err))
+    }
+}
+
+/// An error decoding a row of a statement's results into the row type, with enough
+/// context to find the row (and see what was wrong with it) from the error alone.
+#[derive(Debug)]
+pub struct RowDecodeError {
+    pub query_id: String,
+    /// The token of the page of results the row was in (the last segment of the
+    /// page's `nextUri`), or `None` if it was in the response to the statement itself.
+    pub page: Option<String>,
+    /// The index of the row in the statement's results, counting from 0.
+    pub row_index: usize,
+    /// The column whose value could not be decoded, if the error was in a single column.
+    pub column: Option<Column>,
+    /// The row, as sent by the server.
+    pub row: Value,
+    pub source: PrestinoError,
+}
+
+impl fmt::Display for RowDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Could not decode row {} of query {}",
+            self.row_index, self.query_id
+        )?;
+        if let Some(page) = &self.page {
+            write!(f, " (page {page})")?;
+        }
+        if let Some(column) = &self.column {
+            write!(f, " in column {} ({})", column.name(), column.type_name())?;
+        }
+        write!(f, ": {}; row: {}", self.source, self.row)
+    }
+}
+
+impl StdError for RowDecodeError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        Some(&self.source)
+    }
+}
+
 impl From<reqwest::header::ToStrError> for PrestinoError {
     fn from(_err: reqwest::header::ToStrError) -> Self {
         PrestinoError::HeaderParseError
@@ -0,0 +1,132 @@
+use std::fmt;
+use std::time::Duration;
+
+/// An estimate of the resources a query will use, sent to the server to help it
+/// select a resource group for the query.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ResourceEstimate {
+    /// The wall time the query will take to run.
+    ExecutionTime(Duration),
+    /// The CPU time the query will use.
+    CpuTime(Duration),
+    /// The most memory, in bytes, the query will use at once.
+    PeakMemory(u64),
+}
+
+impl ResourceEstimate {
+    /// The name of the estimate in the `resource-estimate` header.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::ExecutionTime(_) => "EXECUTION_TIME",
+            Self::CpuTime(_) => "CPU_TIME",
+            Self::PeakMemory(_) => "PEAK_MEMORY",
+        }
+    }
+
+    /// The value of the estimate in the `resource-estimate` header, as the server
+    /// parses durations (like `90s`) and data sizes (like `8GB`).
+    pub fn value(&self) -> String {
+        match self {
+            Self::ExecutionTime(duration) | Self::CpuTime(duration) => encode_duration(*duration),
+            Self::PeakMemory(bytes) => encode_data_size(*bytes),
+        }
+    }
+}
+
+impl fmt::Display for ResourceEstimate {
+    /// The estimate as it appears in the `resource-estimate` header, like
+    /// `EXECUTION_TIME=90s`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.name(), self.value())
+    }
+}
+
+const NANOS_PER_SECOND: u128 = 1_000_000_000;
+
+const DURATION_UNITS: [(&str, u128); 7] = [
+    ("d", 86_400 * NANOS_PER_SECOND),
+    ("h", 3_600 * NANOS_PER_SECOND),
+    ("m", 60 * NANOS_PER_SECOND),
+    ("s", NANOS_PER_SECOND),
+    ("ms", 1_000_000),
+    ("us", 1_000),
+    ("ns", 1),
+];
+
+const DATA_SIZE_UNITS: [(&str, u128); 6] = [
+    ("PB", 1 << 50),
+    ("TB", 1 << 40),
+    ("GB", 1 << 30),
+    ("MB", 1 << 20),
+    ("kB", 1 << 10),
+    ("B", 1),
+];
+
+/// Encode a duration exactly, in the largest unit it's a whole number of.
+fn encode_duration(duration: Duration) -> String {
+    if duration.is_zero() {
+        return "0s".to_owned();
+    }
+    encode_in_units(duration.as_nanos(), &DURATION_UNITS)
+}
+
+/// Encode a size exactly, in the largest (binary) unit it's a whole number of.
+fn encode_data_size(bytes: u64) -> String {
+    if bytes == 0 {
+        return "0B".to_owned();
+    }
+    encode_in_units(bytes.into(), &DATA_SIZE_UNITS)
+}
+
+/// Encode a non-zero amount in the largest unit it's a whole number of.  The last
+/// unit is 1, so there always is one.
+// `u128::is_multiple_of` would need Rust 1.87.
+#[allow(unknown_lints, clippy::manual_is_multiple_of)]
+fn encode_in_units(amount: u128, units: &[(&str, u128)]) -> String {
+    let (unit, size) = units
+        .iter()
+        .find(|(_, size)| amount % size == 0)
+        .expect("the smallest unit is 1");
+    format!("{}{unit}", amount / size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_duration() {
+        let encode = |duration| ResourceEstimate::ExecutionTime(duration).value();
+        assert_eq!(encode(Duration::from_secs(2 * 86_400)), "2d");
+        assert_eq!(encode(Duration::from_secs(7_200)), "2h");
+        assert_eq!(encode(Duration::from_secs(5_400)), "90m");
+        assert_eq!(encode(Duration::from_secs(90)), "90s");
+        assert_eq!(encode(Duration::from_millis(1_500)), "1500ms");
+        assert_eq!(encode(Duration::from_micros(1_001)), "1001us");
+        assert_eq!(encode(Duration::from_nanos(7)), "7ns");
+        assert_eq!(encode(Duration::ZERO), "0s");
+    }
+
+    #[test]
+    fn test_encode_data_size() {
+        let encode = |bytes| ResourceEstimate::PeakMemory(bytes).value();
+        assert_eq!(encode(8 << 30), "8GB");
+        assert_eq!(encode(1536 << 20), "1536MB");
+        assert_eq!(encode(3 << 50), "3PB");
+        assert_eq!(encode(1024), "1kB");
+        assert_eq!(encode(1000), "1000B");
+        assert_eq!(encode(0), "0B");
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            ResourceEstimate::CpuTime(Duration::from_secs(3_600)).to_string(),
+            "CPU_TIME=1h"
+        );
+        assert_eq!(
+            ResourceEstimate::PeakMemory(512 << 20).to_string(),
+            "PEAK_MEMORY=512MB"
+        );
+    }
+}
@@ -0,0 +1,56 @@
+//! Helpers for the serde adapters of types that the server sends as strings.
+
+/// Defines a module with a `deserialize` function (for `#[serde(with = "...")]`) that
+/// parses a string value into `$type` with `$parse`, and an `option` submodule for
+/// nullable columns.
+macro_rules! string_adapter {
+    ($(#[$doc:meta])* $name:ident, $type:ty, $parse:path) => {
+        $(#[$doc])*
+        pub mod $name {
+            use super::*;
+            use serde::Deserializer;
+
+            pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<$type, D::Error> {
+                $crate::results::adapter::deserialize_with(deserializer, $parse)
+            }
+
+            pub mod option {
+                use super::*;
+
+                pub fn deserialize<'de, D: Deserializer<'de>>(
+                    deserializer: D,
+                ) -> Result<Option<$type>, D::Error> {
+                    $crate::results::adapter::deserialize_option_with(deserializer, $parse)
+                }
+            }
+        }
+    };
+}
+
+pub(crate) use string_adapter;
+
+pub(crate) fn deserialize_with<'de, D, T>(
+    deserializer: D,
+    parse: fn(&str) -> Result<T, String>,
+) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::{de::Error, Deserialize};
+    let value = String::deserialize(deserializer)?;
+    parse(&value).map_err(D::Error::custom)
+}
+
+pub(crate) fn deserialize_option_with<'de, D, T>(
+    deserializer: D,
+    parse: fn(&str) -> Result<T, String>,
+) -> Result<Option<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::{de::Error, Deserialize};
+    Option::<String>::deserialize(deserializer)?
+        .map(|value| parse(&value))
+        .transpose()
+        .map_err(D::Error::custom)
+}
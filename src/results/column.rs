@@ -1,14 +1,111 @@
+use super::PrestoType;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(from = "RawColumn", into = "RawColumn")]
 pub struct Column {
     name: String,
-    #[serde(rename = "type")]
     type_name: String,
+    type_signature: Option<TypeSignature>,
+    presto_type: PrestoType,
 }
 
 impl Column {
     pub fn name(&self) -> &str {
         &self.name
     }
+
+    /// The name of the column's type, as sent by the server.
+    pub fn type_name(&self) -> &str {
+        &self.type_name
+    }
+
+    pub fn type_signature(&self) -> Option<&TypeSignature> {
+        self.type_signature.as_ref()
+    }
+
+    /// The column's type, parsed from the type name.
+    pub fn presto_type(&self) -> &PrestoType {
+        &self.presto_type
+    }
+}
+
+/// The structured form of a column's type, as sent by the server.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeSignature {
+    pub raw_type: String,
+    #[serde(default)]
+    pub arguments: Vec<serde_json::Value>,
+}
+
+/// The wire format of a Column.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawColumn {
+    name: String,
+    #[serde(rename = "type")]
+    type_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    type_signature: Option<TypeSignature>,
+}
+
+impl From<RawColumn> for Column {
+    fn from(raw: RawColumn) -> Self {
+        Column {
+            presto_type: PrestoType::parse(&raw.type_name),
+            name: raw.name,
+            type_name: raw.type_name,
+            type_signature: raw.type_signature,
+        }
+    }
+}
+
+impl From<Column> for RawColumn {
+    fn from(column: Column) -> Self {
+        RawColumn {
+            name: column.name,
+            type_name: column.type_name,
+            type_signature: column.type_signature,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_deserialize_column() {
+        let column: Column = serde_json::from_value(json!({
+            "name": "a_map",
+            "type": "map(varchar(3), decimal(10,2))",
+            "typeSignature": {
+                "rawType": "map",
+                "arguments": [
+                    {"kind": "TYPE", "value": {"rawType": "varchar", "arguments": [{"kind": "LONG", "value": 3}]}},
+                    {"kind": "TYPE", "value": {"rawType": "decimal", "arguments": [{"kind": "LONG", "value": 10}, {"kind": "LONG", "value": 2}]}}
+                ]
+            }
+        }))
+        .unwrap();
+        assert_eq!(column.name(), "a_map");
+        assert_eq!(column.type_name(), "map(varchar(3), decimal(10,2))");
+        assert_eq!(column.type_signature().unwrap().raw_type, "map");
+        assert_eq!(
+            column.presto_type(),
+            &PrestoType::Map(
+                Box::new(PrestoType::Varchar(Some(3))),
+                Box::new(PrestoType::Decimal {
+                    precision: 10,
+                    scale: 2
+                })
+            )
+        );
+
+        let serialized = serde_json::to_value(&column).unwrap();
+        assert_eq!(serialized["type"], "map(varchar(3), decimal(10,2))");
+        assert_eq!(serialized["typeSignature"]["rawType"], "map");
+    }
 }
@@ -0,0 +1,193 @@
+//! Serde helpers for `DECIMAL` columns.  The server sends decimals as JSON strings so
+//! that no precision is lost; these deserialize them exactly, failing rather than
+//! rounding if the value can't be represented.  Use them on struct fields with
+//! `#[serde(with = "prestino::results::decimal::bigdecimal")]` (or `::option` for
+//! nullable columns).
+//!
+//! `bigdecimal::BigDecimal` can hold any `DECIMAL` value, and can also be used directly
+//! in tuple rows.  `rust_decimal::Decimal` holds at most 28 significant digits, so
+//! larger `DECIMAL(38, s)` values will be an error with these helpers.  Note that
+//! `Decimal`'s own `Deserialize` implementation rounds such values instead.
+
+#[cfg(feature = "rust_decimal")]
+pub mod rust_decimal {
+    use ::rust_decimal::Decimal;
+    use serde::de::{self, Deserialize, Deserializer, Visitor};
+    use std::fmt;
+
+    /// Deserialize a `Decimal` exactly from a `DECIMAL` column.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Decimal, D::Error> {
+        deserializer.deserialize_any(ExactDecimalVisitor)
+    }
+
+    pub mod option {
+        use super::ExactDecimal;
+        use ::rust_decimal::Decimal;
+        use serde::{Deserialize, Deserializer};
+
+        /// Deserialize an `Option<Decimal>` exactly from a nullable `DECIMAL` column.
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<Decimal>, D::Error> {
+            Ok(Option::<ExactDecimal>::deserialize(deserializer)?.map(|d| d.0))
+        }
+    }
+
+    struct ExactDecimal(Decimal);
+
+    impl<'de> Deserialize<'de> for ExactDecimal {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            super::rust_decimal::deserialize(deserializer).map(ExactDecimal)
+        }
+    }
+
+    struct ExactDecimalVisitor;
+
+    impl<'de> Visitor<'de> for ExactDecimalVisitor {
+        type Value = Decimal;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a decimal string")
+        }
+
+        fn visit_str<E: de::Error>(self, value: &str) -> Result<Decimal, E> {
+            Decimal::from_str_exact(value)
+                .map_err(|err| E::custom(format!("invalid decimal {value}: {err}")))
+        }
+
+        fn visit_i64<E: de::Error>(self, value: i64) -> Result<Decimal, E> {
+            Ok(Decimal::from(value))
+        }
+
+        fn visit_u64<E: de::Error>(self, value: u64) -> Result<Decimal, E> {
+            Ok(Decimal::from(value))
+        }
+    }
+}
+
+#[cfg(feature = "bigdecimal")]
+pub mod bigdecimal {
+    use ::bigdecimal::BigDecimal;
+    use serde::de::{self, Deserialize, Deserializer, Visitor};
+    use std::fmt;
+    use std::str::FromStr;
+
+    /// Deserialize a `BigDecimal` exactly from a `DECIMAL` column.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BigDecimal, D::Error> {
+        deserializer.deserialize_any(ExactBigDecimalVisitor)
+    }
+
+    pub mod option {
+        use super::ExactBigDecimal;
+        use ::bigdecimal::BigDecimal;
+        use serde::{Deserialize, Deserializer};
+
+        /// Deserialize an `Option<BigDecimal>` exactly from a nullable `DECIMAL` column.
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<BigDecimal>, D::Error> {
+            Ok(Option::<ExactBigDecimal>::deserialize(deserializer)?.map(|d| d.0))
+        }
+    }
+
+    struct ExactBigDecimal(BigDecimal);
+
+    impl<'de> Deserialize<'de> for ExactBigDecimal {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            super::bigdecimal::deserialize(deserializer).map(ExactBigDecimal)
+        }
+    }
+
+    struct ExactBigDecimalVisitor;
+
+    impl<'de> Visitor<'de> for ExactBigDecimalVisitor {
+        type Value = BigDecimal;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a decimal string")
+        }
+
+        fn visit_str<E: de::Error>(self, value: &str) -> Result<BigDecimal, E> {
+            BigDecimal::from_str(value)
+                .map_err(|err| E::custom(format!("invalid decimal {value}: {err}")))
+        }
+
+        fn visit_i64<E: de::Error>(self, value: i64) -> Result<BigDecimal, E> {
+            Ok(BigDecimal::from(value))
+        }
+
+        fn visit_u64<E: de::Error>(self, value: u64) -> Result<BigDecimal, E> {
+            Ok(BigDecimal::from(value))
+        }
+    }
+}
+
+#[cfg(all(test, any(feature = "rust_decimal", feature = "bigdecimal")))]
+mod tests {
+    use serde::Deserialize;
+    use serde_json::json;
+
+    const MAX_DECIMAL: &str = "9999999999999999999999999999.9999999999";
+    const MIN_DECIMAL: &str = "-9999999999999999999999999999.9999999999";
+
+    #[cfg(feature = "bigdecimal")]
+    #[test]
+    fn test_bigdecimal() {
+        use ::bigdecimal::BigDecimal;
+        use std::str::FromStr;
+
+        #[derive(Deserialize)]
+        struct Row {
+            #[serde(with = "super::bigdecimal")]
+            a: BigDecimal,
+            #[serde(with = "super::bigdecimal::option")]
+            b: Option<BigDecimal>,
+        }
+
+        let row: Row = serde_json::from_value(json!([MAX_DECIMAL, MIN_DECIMAL])).unwrap();
+        assert_eq!(row.a, BigDecimal::from_str(MAX_DECIMAL).unwrap());
+        assert_eq!(row.a.to_string(), MAX_DECIMAL);
+        assert_eq!(row.b.unwrap().to_string(), MIN_DECIMAL);
+
+        let row: Row = serde_json::from_value(json!(["-0.0000000001", null])).unwrap();
+        assert_eq!(row.a, BigDecimal::from_str("-0.0000000001").unwrap());
+        assert!(row.a < BigDecimal::default());
+        assert_eq!(row.b, None);
+
+        assert!(serde_json::from_value::<Row>(json!(["abc", null])).is_err());
+
+        // BigDecimal can be used directly in tuple rows.
+        let row: (BigDecimal,) = serde_json::from_value(json!([MAX_DECIMAL])).unwrap();
+        assert_eq!(row.0.to_string(), MAX_DECIMAL);
+    }
+
+    #[cfg(feature = "rust_decimal")]
+    #[test]
+    fn test_rust_decimal() {
+        use ::rust_decimal::Decimal;
+        use std::str::FromStr;
+
+        #[derive(Deserialize)]
+        struct Row {
+            #[serde(with = "super::rust_decimal")]
+            a: Decimal,
+            #[serde(with = "super::rust_decimal::option")]
+            b: Option<Decimal>,
+        }
+
+        let max = "999999999999999999.9999999999";
+        let row: Row = serde_json::from_value(json!([max, "-12.3400"])).unwrap();
+        assert_eq!(row.a, Decimal::from_str(max).unwrap());
+        assert_eq!(row.b.unwrap().to_string(), "-12.3400");
+
+        let row: Row = serde_json::from_value(json!([5, null])).unwrap();
+        assert_eq!(row.a, Decimal::from(5));
+        assert_eq!(row.b, None);
+
+        // Too many digits for a Decimal is an error, rather than rounding.
+        let result = serde_json::from_value::<Row>(json!([MAX_DECIMAL, null]));
+        assert!(result.is_err());
+        let result = serde_json::from_value::<Row>(json!(["1", MIN_DECIMAL]));
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,96 @@
+//! Serde helpers for interval columns.  The server sends an `interval day to second`
+//! like `1 02:03:04.567` (days, then hours to milliseconds), and an
+//! `interval year to month` like `1-2` (years, then months); either may be negative.
+
+use super::adapter::string_adapter;
+use std::time::Duration;
+
+string_adapter!(
+    /// An `interval day to second` column as a `Duration`.  Since a `Duration` can't
+    /// be negative, negative intervals are an error.
+    day_to_second,
+    Duration,
+    parse_day_to_second
+);
+
+string_adapter!(
+    /// An `interval year to month` column as a (possibly negative) number of months.
+    year_to_month,
+    i32,
+    parse_year_to_month
+);
+
+fn parse_day_to_second(value: &str) -> Result<Duration, String> {
+    if value.starts_with('-') {
+        return Err(format!("negative interval '{value}' can't be a Duration"));
+    }
+    parse_days(value).ok_or_else(|| format!("invalid interval day to second '{value}'"))
+}
+
+fn parse_days(value: &str) -> Option<Duration> {
+    let (days, time) = value.split_once(' ')?;
+    let (hms, millis) = time.split_once('.').unwrap_or((time, "000"));
+    let mut parts = hms.splitn(3, ':');
+    let hours: u64 = parts.next()?.parse().ok()?;
+    let minutes: u64 = parts.next()?.parse().ok()?;
+    let seconds: u64 = parts.next()?.parse().ok()?;
+    let days: u64 = days.parse().ok()?;
+    if minutes >= 60 || seconds >= 60 || millis.len() != 3 {
+        return None;
+    }
+    let seconds = ((days * 24 + hours) * 60 + minutes) * 60 + seconds;
+    Some(Duration::from_secs(seconds) + Duration::from_millis(millis.parse().ok()?))
+}
+
+fn parse_year_to_month(value: &str) -> Result<i32, String> {
+    let (sign, unsigned) = match value.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, value),
+    };
+    unsigned
+        .split_once('-')
+        .and_then(|(years, months)| {
+            let years: i32 = years.parse().ok()?;
+            let months: i32 = months.parse().ok()?;
+            years.checked_mul(12)?.checked_add(months)
+        })
+        .map(|months| sign * months)
+        .ok_or_else(|| format!("invalid interval year to month '{value}'"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_day_to_second() {
+        let parse = |value| day_to_second::deserialize(json!(value));
+        assert_eq!(
+            parse("1 02:03:04.567").unwrap(),
+            Duration::from_millis(93_784_567)
+        );
+        assert_eq!(parse("0 00:00:00.000").unwrap(), Duration::ZERO);
+        assert_eq!(parse("2 00:00:00").unwrap(), Duration::from_secs(172_800));
+        assert!(parse("-1 02:03:04.567").is_err());
+        assert!(parse("02:03:04.567").is_err());
+        assert!(parse("1 02:63:04.567").is_err());
+        assert_eq!(
+            day_to_second::option::deserialize(json!(null)).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_year_to_month() {
+        let parse = |value| year_to_month::deserialize(json!(value));
+        assert_eq!(parse("1-2").unwrap(), 14);
+        assert_eq!(parse("-3-0").unwrap(), -36);
+        assert_eq!(parse("0-11").unwrap(), 11);
+        assert!(parse("14").is_err());
+        assert_eq!(
+            year_to_month::option::deserialize(json!("0-1")).unwrap(),
+            Some(1)
+        );
+    }
+}
@@ -0,0 +1,55 @@
+//! Serde helpers for `ipaddress` columns.  The server sends addresses like
+//! `192.168.0.1` or `2001:db8::1`; IPv4-mapped IPv6 addresses like `::ffff:192.168.0.1`
+//! are decoded as IPv4 addresses, as the server considers them equal.
+
+use super::adapter::{deserialize_option_with, deserialize_with};
+use serde::Deserializer;
+use std::net::IpAddr;
+
+/// Deserialize an `IpAddr` from an `ipaddress` column.
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<IpAddr, D::Error> {
+    deserialize_with(deserializer, parse_ipaddress)
+}
+
+pub mod option {
+    use super::*;
+
+    /// Deserialize an `Option<IpAddr>` from a nullable `ipaddress` column.
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<IpAddr>, D::Error> {
+        deserialize_option_with(deserializer, parse_ipaddress)
+    }
+}
+
+fn parse_ipaddress(value: &str) -> Result<IpAddr, String> {
+    let address: IpAddr = value
+        .parse()
+        .map_err(|err| format!("invalid ipaddress '{value}': {err}"))?;
+    Ok(address.to_canonical())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    #[test]
+    fn test_ipaddress() {
+        assert_eq!(
+            deserialize(json!("192.168.0.1")).unwrap(),
+            IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1))
+        );
+        assert_eq!(
+            deserialize(json!("::ffff:192.168.0.1")).unwrap(),
+            IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1))
+        );
+        assert_eq!(
+            deserialize(json!("2001:db8::1")).unwrap(),
+            IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1))
+        );
+        assert!(deserialize(json!("192.168.0.256")).is_err());
+        assert_eq!(option::deserialize(json!(null)).unwrap(), None);
+    }
+}
@@ -0,0 +1,48 @@
+//! Serde helpers for `json` columns.  The server sends a JSON value as a string of
+//! JSON text, so a `serde_json::Value` field would otherwise just hold that string.
+
+use super::adapter::{deserialize_option_with, deserialize_with};
+use serde::Deserializer;
+use serde_json::Value;
+
+/// Deserialize the parsed JSON of a `json` column.
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Value, D::Error> {
+    deserialize_with(deserializer, parse_json)
+}
+
+pub mod option {
+    use super::*;
+
+    /// Deserialize the parsed JSON of a nullable `json` column.  Note that a SQL `NULL`
+    /// is `None`, while a JSON `null` is `Some(Value::Null)`.
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Value>, D::Error> {
+        deserialize_option_with(deserializer, parse_json)
+    }
+}
+
+fn parse_json(value: &str) -> Result<Value, String> {
+    serde_json::from_str(value).map_err(|err| format!("invalid json '{value}': {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_json() {
+        assert_eq!(
+            deserialize(json!("{\"a\":[1,true]}")).unwrap(),
+            json!({"a": [1, true]})
+        );
+        assert_eq!(deserialize(json!("\"text\"")).unwrap(), json!("text"));
+        assert!(deserialize(json!("{\"a\":")).is_err());
+        assert_eq!(option::deserialize(json!(null)).unwrap(), None);
+        assert_eq!(
+            option::deserialize(json!("null")).unwrap(),
+            Some(Value::Null)
+        );
+    }
+}
@@ -1,9 +1,22 @@
+mod adapter;
 mod column;
+pub mod decimal;
+pub mod interval;
+pub mod ipaddress;
+pub mod json;
+mod presto_type;
 mod query_error;
 mod query_results;
 mod query_stats;
+pub mod temporal;
+#[cfg(feature = "uuid")]
+pub mod uuid;
+mod value;
+pub mod varbinary;
 
-pub use column::Column;
+pub use column::{Column, TypeSignature};
+pub use presto_type::{PrestoType, RowField};
 pub use query_error::{ErrorLocation, QueryError};
 pub use query_results::QueryResults;
 pub use query_stats::QueryStats;
+pub use value::Value;
@@ -0,0 +1,418 @@
+/// A Presto/Trino type, parsed from the type name the server sends with each column,
+/// such as `bigint`, `decimal(10,2)`, `array(varchar)` or `timestamp(6) with time zone`.
+///
+/// Types that prestino doesn't know about are kept as `Other`, with their full name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrestoType {
+    Boolean,
+    TinyInt,
+    SmallInt,
+    Integer,
+    BigInt,
+    Real,
+    Double,
+    Decimal {
+        precision: u32,
+        scale: u32,
+    },
+    /// A varchar with an optional maximum length.
+    Varchar(Option<u32>),
+    Char(u32),
+    Varbinary,
+    Json,
+    Date,
+    Time(u32),
+    TimeWithTimeZone(u32),
+    Timestamp(u32),
+    TimestampWithTimeZone(u32),
+    IntervalDayToSecond,
+    IntervalYearToMonth,
+    Uuid,
+    IpAddress,
+    Array(Box<PrestoType>),
+    Map(Box<PrestoType>, Box<PrestoType>),
+    Row(Vec<RowField>),
+    /// The type of a `NULL` literal.
+    Unknown,
+    Other(String),
+}
+
+/// A field of a `row` type.  Fields may be anonymous, as in `row(bigint, varchar)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowField {
+    pub name: Option<String>,
+    pub field_type: PrestoType,
+}
+
+/// The precision of `time` and `timestamp` types when none is given.
+const DEFAULT_TIME_PRECISION: u32 = 3;
+/// The precision of `decimal` when none is given.
+const DEFAULT_DECIMAL_PRECISION: u32 = 38;
+
+impl PrestoType {
+    /// Parse a type name as sent by the server.  This never fails; any type that
+    /// can't be parsed is returned as `PrestoType::Other`.
+    pub fn parse(type_name: &str) -> PrestoType {
+        let mut parser = TypeParser {
+            input: type_name,
+            pos: 0,
+        };
+        match parser.parse_type() {
+            Some(presto_type) if parser.at_end() => presto_type,
+            _ => PrestoType::Other(type_name.to_owned()),
+        }
+    }
+}
+
+struct TypeParser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> TypeParser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn at_end(&mut self) -> bool {
+        self.skip_whitespace();
+        self.rest().is_empty()
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.rest().chars().next()
+    }
+
+    /// Consume `c` if it is the next non-whitespace character.
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Option<()> {
+        self.eat(c).then_some(())
+    }
+
+    fn word(&mut self) -> Option<&'a str> {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return None;
+        }
+        self.pos += len;
+        Some(&rest[..len])
+    }
+
+    /// Consume the given sequence of words (case-insensitively), if they are next.
+    fn eat_words(&mut self, words: &[&str]) -> bool {
+        let start = self.pos;
+        for expected in words {
+            if !matches!(self.word(), Some(word) if word.eq_ignore_ascii_case(expected)) {
+                self.pos = start;
+                return false;
+            }
+        }
+        true
+    }
+
+    fn number(&mut self) -> Option<u32> {
+        self.word()?.parse().ok()
+    }
+
+    /// An optional parenthesized number, like the `(3)` of `timestamp(3)`.
+    fn optional_number(&mut self) -> Option<Option<u32>> {
+        if !self.eat('(') {
+            return Some(None);
+        }
+        let number = self.number()?;
+        self.expect(')')?;
+        Some(Some(number))
+    }
+
+    fn with_time_zone(&mut self) -> bool {
+        if self.eat_words(&["with", "time", "zone"]) {
+            return true;
+        }
+        self.eat_words(&["without", "time", "zone"]);
+        false
+    }
+
+    fn parse_type(&mut self) -> Option<PrestoType> {
+        let start = self.pos;
+        let name = self.word()?.to_ascii_lowercase();
+        let presto_type = match name.as_str() {
+            "boolean" => PrestoType::Boolean,
+            "tinyint" => PrestoType::TinyInt,
+            "smallint" => PrestoType::SmallInt,
+            "integer" | "int" => PrestoType::Integer,
+            "bigint" => PrestoType::BigInt,
+            "real" => PrestoType::Real,
+            "double" => {
+                self.eat_words(&["precision"]);
+                PrestoType::Double
+            }
+            "decimal" => {
+                let (precision, scale) = if self.eat('(') {
+                    let precision = self.number()?;
+                    let scale = if self.eat(',') { self.number()? } else { 0 };
+                    self.expect(')')?;
+                    (precision, scale)
+                } else {
+                    (DEFAULT_DECIMAL_PRECISION, 0)
+                };
+                PrestoType::Decimal { precision, scale }
+            }
+            "varchar" => PrestoType::Varchar(self.optional_number()?),
+            "char" => PrestoType::Char(self.optional_number()?.unwrap_or(1)),
+            "varbinary" => PrestoType::Varbinary,
+            "json" => PrestoType::Json,
+            "date" => PrestoType::Date,
+            "time" => {
+                let precision = self.optional_number()?.unwrap_or(DEFAULT_TIME_PRECISION);
+                if self.with_time_zone() {
+                    PrestoType::TimeWithTimeZone(precision)
+                } else {
+                    PrestoType::Time(precision)
+                }
+            }
+            "timestamp" => {
+                let precision = self.optional_number()?.unwrap_or(DEFAULT_TIME_PRECISION);
+                if self.with_time_zone() {
+                    PrestoType::TimestampWithTimeZone(precision)
+                } else {
+                    PrestoType::Timestamp(precision)
+                }
+            }
+            "interval" => {
+                if self.eat_words(&["day", "to", "second"]) {
+                    PrestoType::IntervalDayToSecond
+                } else if self.eat_words(&["year", "to", "month"]) {
+                    PrestoType::IntervalYearToMonth
+                } else {
+                    return None;
+                }
+            }
+            "uuid" => PrestoType::Uuid,
+            "ipaddress" => PrestoType::IpAddress,
+            "unknown" => PrestoType::Unknown,
+            "array" => {
+                self.expect('(')?;
+                let element_type = self.parse_type()?;
+                self.expect(')')?;
+                PrestoType::Array(Box::new(element_type))
+            }
+            "map" => {
+                self.expect('(')?;
+                let key_type = self.parse_type()?;
+                self.expect(',')?;
+                let value_type = self.parse_type()?;
+                self.expect(')')?;
+                PrestoType::Map(Box::new(key_type), Box::new(value_type))
+            }
+            "row" => {
+                self.expect('(')?;
+                let mut fields = vec![self.parse_row_field()?];
+                while self.eat(',') {
+                    fields.push(self.parse_row_field()?);
+                }
+                self.expect(')')?;
+                PrestoType::Row(fields)
+            }
+            _ => {
+                // Keep any parameters, like the `(4326)` of `geometry(4326)`.
+                if self.peek() == Some('(') {
+                    let mut depth = 0;
+                    for (idx, c) in self.rest().char_indices() {
+                        match c {
+                            '(' => depth += 1,
+                            ')' => depth -= 1,
+                            _ => (),
+                        }
+                        if depth == 0 {
+                            self.pos += idx + 1;
+                            break;
+                        }
+                    }
+                    if depth != 0 {
+                        return None;
+                    }
+                }
+                PrestoType::Other(self.input[start..self.pos].trim().to_owned())
+            }
+        };
+        Some(presto_type)
+    }
+
+    fn parse_row_field(&mut self) -> Option<RowField> {
+        if self.peek() == Some('"') {
+            let name = self.quoted_name()?;
+            let field_type = self.parse_type()?;
+            return Some(RowField {
+                name: Some(name),
+                field_type,
+            });
+        }
+
+        // Fields are either `type` or `name type`; try the former first.
+        let start = self.pos;
+        if let Some(field_type) = self.parse_type() {
+            if matches!(self.peek(), Some(',') | Some(')')) {
+                return Some(RowField {
+                    name: None,
+                    field_type,
+                });
+            }
+        }
+        self.pos = start;
+        let name = self.word()?.to_owned();
+        let field_type = self.parse_type()?;
+        Some(RowField {
+            name: Some(name),
+            field_type,
+        })
+    }
+
+    /// A double-quoted name, where `""` is an escaped quote.
+    fn quoted_name(&mut self) -> Option<String> {
+        self.expect('"')?;
+        let mut name = String::new();
+        let mut chars = self.rest().char_indices().peekable();
+        while let Some((idx, c)) = chars.next() {
+            if c == '"' {
+                if matches!(chars.peek(), Some((_, '"'))) {
+                    chars.next();
+                } else {
+                    self.pos += idx + 1;
+                    return Some(name);
+                }
+            }
+            name.push(c);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(name: Option<&str>, field_type: PrestoType) -> RowField {
+        RowField {
+            name: name.map(str::to_owned),
+            field_type,
+        }
+    }
+
+    #[test]
+    fn test_parse_simple() {
+        assert_eq!(PrestoType::parse("bigint"), PrestoType::BigInt);
+        assert_eq!(PrestoType::parse("BOOLEAN"), PrestoType::Boolean);
+        assert_eq!(PrestoType::parse("varchar"), PrestoType::Varchar(None));
+        assert_eq!(
+            PrestoType::parse("varchar(10)"),
+            PrestoType::Varchar(Some(10))
+        );
+        assert_eq!(PrestoType::parse("char(3)"), PrestoType::Char(3));
+        assert_eq!(
+            PrestoType::parse("decimal(38, 10)"),
+            PrestoType::Decimal {
+                precision: 38,
+                scale: 10
+            }
+        );
+        assert_eq!(
+            PrestoType::parse("interval day to second"),
+            PrestoType::IntervalDayToSecond
+        );
+        assert_eq!(
+            PrestoType::parse("interval year to month"),
+            PrestoType::IntervalYearToMonth
+        );
+        assert_eq!(
+            PrestoType::parse("HyperLogLog"),
+            PrestoType::Other("HyperLogLog".to_owned())
+        );
+        assert_eq!(
+            PrestoType::parse("geometry(4326)"),
+            PrestoType::Other("geometry(4326)".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_parse_temporal() {
+        assert_eq!(PrestoType::parse("date"), PrestoType::Date);
+        assert_eq!(PrestoType::parse("time"), PrestoType::Time(3));
+        assert_eq!(
+            PrestoType::parse("time(9) with time zone"),
+            PrestoType::TimeWithTimeZone(9)
+        );
+        assert_eq!(
+            PrestoType::parse("timestamp(12)"),
+            PrestoType::Timestamp(12)
+        );
+        assert_eq!(
+            PrestoType::parse("timestamp with time zone"),
+            PrestoType::TimestampWithTimeZone(3)
+        );
+        assert_eq!(
+            PrestoType::parse("timestamp(6) with time zone"),
+            PrestoType::TimestampWithTimeZone(6)
+        );
+    }
+
+    #[test]
+    fn test_parse_nested() {
+        assert_eq!(
+            PrestoType::parse("array(map(varchar, array(timestamp(3) with time zone)))"),
+            PrestoType::Array(Box::new(PrestoType::Map(
+                Box::new(PrestoType::Varchar(None)),
+                Box::new(PrestoType::Array(Box::new(
+                    PrestoType::TimestampWithTimeZone(3)
+                )))
+            )))
+        );
+        assert_eq!(
+            PrestoType::parse(
+                r#"row(a bigint, "b ""c""" varchar(2), date date, row(double, interval day to second))"#
+            ),
+            PrestoType::Row(vec![
+                field(Some("a"), PrestoType::BigInt),
+                field(Some("b \"c\""), PrestoType::Varchar(Some(2))),
+                field(Some("date"), PrestoType::Date),
+                field(
+                    None,
+                    PrestoType::Row(vec![
+                        field(None, PrestoType::Double),
+                        field(None, PrestoType::IntervalDayToSecond),
+                    ])
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert_eq!(
+            PrestoType::parse("array(bigint"),
+            PrestoType::Other("array(bigint".to_owned())
+        );
+        assert_eq!(
+            PrestoType::parse("bigint)"),
+            PrestoType::Other("bigint)".to_owned())
+        );
+        assert_eq!(PrestoType::parse(""), PrestoType::Other("".to_owned()));
+    }
+}
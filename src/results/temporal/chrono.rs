@@ -0,0 +1,233 @@
+//! Adapters for `chrono` types.  Named zones are resolved with `chrono-tz`.
+
+use super::parse::{self, invalid, DateParts, TimeParts, Zone};
+use crate::results::adapter::string_adapter;
+use ::chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone};
+use chrono_tz::{Tz, TZ_VARIANTS};
+
+string_adapter!(
+    /// A `date` column as a `NaiveDate`.
+    date,
+    NaiveDate,
+    parse_date
+);
+
+string_adapter!(
+    /// A `time(p)` column as a `NaiveTime`.
+    time,
+    NaiveTime,
+    parse_time
+);
+
+string_adapter!(
+    /// A `time(p) with time zone` column as a `NaiveTime` and its offset.
+    time_with_time_zone,
+    (NaiveTime, FixedOffset),
+    parse_time_with_time_zone
+);
+
+string_adapter!(
+    /// A `timestamp(p)` column as a `NaiveDateTime`.
+    timestamp,
+    NaiveDateTime,
+    parse_timestamp
+);
+
+string_adapter!(
+    /// A `timestamp(p) with time zone` column as a `DateTime` with the value's offset.
+    timestamp_with_time_zone,
+    DateTime<FixedOffset>,
+    parse_timestamp_with_time_zone
+);
+
+string_adapter!(
+    /// A `timestamp(p)` column as a `DateTime` in the session time zone, which must
+    /// be set with `Headers::set_timezone`.
+    timestamp_in_session_zone,
+    DateTime<FixedOffset>,
+    parse_timestamp_in_session_zone
+);
+
+fn parse_date(value: &str) -> Result<NaiveDate, String> {
+    parse::parse_date(value)
+        .and_then(to_date)
+        .ok_or_else(|| invalid("date", value))
+}
+
+fn parse_time(value: &str) -> Result<NaiveTime, String> {
+    parse::parse_time(value)
+        .and_then(to_time)
+        .ok_or_else(|| invalid("time", value))
+}
+
+fn parse_time_with_time_zone(value: &str) -> Result<(NaiveTime, FixedOffset), String> {
+    parse::parse_time_with_time_zone(value)
+        .and_then(|(time, offset)| Some((to_time(time)?, FixedOffset::east_opt(offset)?)))
+        .ok_or_else(|| invalid("time with time zone", value))
+}
+
+fn parse_timestamp(value: &str) -> Result<NaiveDateTime, String> {
+    parse::parse_timestamp(value)
+        .and_then(|(date, time)| to_datetime(date, time))
+        .ok_or_else(|| invalid("timestamp", value))
+}
+
+fn parse_timestamp_with_time_zone(value: &str) -> Result<DateTime<FixedOffset>, String> {
+    parse::parse_timestamp_with_time_zone(value)
+        .and_then(|(date, time, zone)| in_zone(to_datetime(date, time)?, &zone))
+        .ok_or_else(|| invalid("timestamp with time zone", value))
+}
+
+fn parse_timestamp_in_session_zone(value: &str) -> Result<DateTime<FixedOffset>, String> {
+    let session_zone = super::session_time_zone()?;
+    let zone = parse::parse_zone(&session_zone)
+        .ok_or_else(|| format!("invalid session time zone '{session_zone}'"))?;
+    let datetime = parse_timestamp(value)?;
+    in_zone(datetime, &zone)
+        .ok_or_else(|| format!("timestamp '{value}' does not exist in time zone '{session_zone}'"))
+}
+
+fn to_date(date: DateParts) -> Option<NaiveDate> {
+    NaiveDate::from_ymd_opt(date.year, date.month.into(), date.day.into())
+}
+
+fn to_time(time: TimeParts) -> Option<NaiveTime> {
+    NaiveTime::from_hms_nano_opt(
+        time.hour.into(),
+        time.minute.into(),
+        time.second.into(),
+        time.nanosecond,
+    )
+}
+
+fn to_datetime(date: DateParts, time: TimeParts) -> Option<NaiveDateTime> {
+    Some(NaiveDateTime::new(to_date(date)?, to_time(time)?))
+}
+
+/// The instant of the local `datetime` in `zone`.  If the local time is ambiguous
+/// (when clocks go back), this is the earlier instant, as on the server.
+fn in_zone(datetime: NaiveDateTime, zone: &Zone) -> Option<DateTime<FixedOffset>> {
+    match zone {
+        Zone::Offset(seconds) => FixedOffset::east_opt(*seconds)?
+            .from_local_datetime(&datetime)
+            .single(),
+        Zone::Named(name) => {
+            let zoned = find_zone(name)?.from_local_datetime(&datetime).earliest()?;
+            Some(zoned.with_timezone(&zoned.offset().fix()))
+        }
+    }
+}
+
+/// Zone names from the server are canonical, but the session time zone is lowercased
+/// in the request headers.
+fn find_zone(name: &str) -> Option<Tz> {
+    name.parse().ok().or_else(|| {
+        TZ_VARIANTS
+            .iter()
+            .copied()
+            .find(|tz| tz.name().eq_ignore_ascii_case(name))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::results::temporal::with_session_time_zone;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[test]
+    fn test_dates_and_times() {
+        #[derive(Deserialize)]
+        struct Row {
+            #[serde(with = "date")]
+            date: NaiveDate,
+            #[serde(with = "time")]
+            time: NaiveTime,
+            #[serde(with = "time::option")]
+            null_time: Option<NaiveTime>,
+            #[serde(with = "time_with_time_zone")]
+            time_tz: (NaiveTime, FixedOffset),
+        }
+
+        let row: Row = serde_json::from_value(json!([
+            "2022-11-28",
+            "03:52:42.123456789012",
+            null,
+            "03:52:42.123+05:30"
+        ]))
+        .unwrap();
+        assert_eq!(row.date, NaiveDate::from_ymd_opt(2022, 11, 28).unwrap());
+        assert_eq!(
+            row.time,
+            NaiveTime::from_hms_nano_opt(3, 52, 42, 123_456_789).unwrap()
+        );
+        assert_eq!(row.null_time, None);
+        assert_eq!(
+            row.time_tz,
+            (
+                NaiveTime::from_hms_milli_opt(3, 52, 42, 123).unwrap(),
+                FixedOffset::east_opt(19800).unwrap()
+            )
+        );
+
+        let result =
+            serde_json::from_value::<Row>(json!(["2022-02-30", "00:00:00", null, "00:00:00Z"]));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_timestamps() {
+        #[derive(Deserialize)]
+        struct Row {
+            #[serde(with = "timestamp")]
+            ts: NaiveDateTime,
+            #[serde(with = "timestamp_with_time_zone")]
+            named: DateTime<FixedOffset>,
+            #[serde(with = "timestamp_with_time_zone")]
+            offset: DateTime<FixedOffset>,
+            #[serde(with = "timestamp_with_time_zone::option")]
+            utc: Option<DateTime<FixedOffset>>,
+        }
+
+        let row: Row = serde_json::from_value(json!([
+            "2022-11-28 03:52:42.123456789012",
+            "2022-11-28 03:52:42.123 America/Los_Angeles",
+            "2022-07-01 12:00:00 -03:30",
+            "2022-11-28 03:52:42 UTC",
+        ]))
+        .unwrap();
+        assert_eq!(row.ts.to_string(), "2022-11-28 03:52:42.123456789");
+        assert_eq!(row.named.to_rfc3339(), "2022-11-28T03:52:42.123-08:00");
+        assert_eq!(row.offset.to_rfc3339(), "2022-07-01T12:00:00-03:30");
+        assert_eq!(row.utc.unwrap().to_rfc3339(), "2022-11-28T03:52:42+00:00");
+
+        // The offset of a named zone depends on the date.
+        let summer: DateTime<FixedOffset> = timestamp_with_time_zone::deserialize(json!(
+            "2022-07-01 00:00:00.000 America/Los_Angeles"
+        ))
+        .unwrap();
+        assert_eq!(summer.to_rfc3339(), "2022-07-01T00:00:00-07:00");
+
+        let result = timestamp_with_time_zone::deserialize(json!("2022-07-01 00:00:00 Mars/Base"));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_timestamp_in_session_zone() {
+        let value = json!("2022-07-01 12:00:00.000");
+        assert!(timestamp_in_session_zone::deserialize(value.clone()).is_err());
+
+        let datetime = with_session_time_zone(Some("america/los_angeles"), || {
+            timestamp_in_session_zone::deserialize(value.clone())
+        })
+        .unwrap();
+        assert_eq!(datetime.to_rfc3339(), "2022-07-01T12:00:00-07:00");
+
+        let datetime = with_session_time_zone(Some("+05:30"), || {
+            timestamp_in_session_zone::deserialize(value)
+        })
+        .unwrap();
+        assert_eq!(datetime.to_rfc3339(), "2022-07-01T12:00:00+05:30");
+    }
+}
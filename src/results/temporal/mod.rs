@@ -0,0 +1,59 @@
+//! Serde helpers for temporal columns, behind the `chrono` and `time` features.  The
+//! server sends temporal values as strings, like `2022-11-28` for a `date`,
+//! `03:52:42.123` for a `time(3)` and `2022-11-28 03:52:42.123 America/Los_Angeles` for
+//! a `timestamp(3) with time zone`.  Each module here deserializes one of these types;
+//! use them on struct fields like
+//! `#[serde(with = "prestino::results::temporal::chrono::timestamp")]`, or
+//! `...::timestamp::option` for nullable columns.
+//!
+//! Fractional seconds are sent with the column's precision, up to picoseconds for
+//! `timestamp(12)`.  Neither `chrono` nor `time` can represent more than nanoseconds,
+//! so any further digits are truncated.
+//!
+//! A `timestamp with time zone` is converted to a fixed offset.  Named zones are
+//! resolved with the IANA time zone database, using the offset in effect at that instant.
+//! Values created by the server in the session time zone (e.g. by `current_timestamp`,
+//! or by casting a `timestamp`) are in the zone given by `Headers::set_timezone`.  The
+//! `timestamp_in_session_zone` helpers interpret a `timestamp` column in that zone.
+
+use std::cell::RefCell;
+
+#[cfg(feature = "chrono")]
+pub mod chrono;
+#[cfg(any(feature = "chrono", feature = "time"))]
+mod parse;
+#[cfg(feature = "time")]
+pub mod time;
+
+thread_local! {
+    static SESSION_TIME_ZONE: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Run `f` (which deserializes rows) with the session time zone available to the
+/// `timestamp_in_session_zone` helpers.
+pub(crate) fn with_session_time_zone<R>(time_zone: Option<&str>, f: impl FnOnce() -> R) -> R {
+    let previous = SESSION_TIME_ZONE.with(|zone| zone.replace(time_zone.map(ToOwned::to_owned)));
+    let result = f();
+    SESSION_TIME_ZONE.with(|zone| zone.replace(previous));
+    result
+}
+
+#[cfg(any(feature = "chrono", feature = "time"))]
+fn session_time_zone() -> Result<String, String> {
+    SESSION_TIME_ZONE
+        .with(|zone| zone.borrow().clone())
+        .ok_or_else(|| "no session time zone is set".to_owned())
+}
+
+#[cfg(all(test, any(feature = "chrono", feature = "time")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_time_zone() {
+        assert!(session_time_zone().is_err());
+        let zone = with_session_time_zone(Some("america/chicago"), session_time_zone);
+        assert_eq!(zone.unwrap(), "america/chicago");
+        assert!(session_time_zone().is_err());
+    }
+}
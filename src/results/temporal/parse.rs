@@ -0,0 +1,188 @@
+/// The fields of a date, as sent by the server.
+#[derive(Debug, PartialEq)]
+pub(super) struct DateParts {
+    pub(super) year: i32,
+    pub(super) month: u8,
+    pub(super) day: u8,
+}
+
+/// The fields of a time of day, with fractional seconds truncated to nanoseconds.
+#[derive(Debug, PartialEq)]
+pub(super) struct TimeParts {
+    pub(super) hour: u8,
+    pub(super) minute: u8,
+    pub(super) second: u8,
+    pub(super) nanosecond: u32,
+}
+
+/// The zone of a `with time zone` value.
+#[derive(Debug, PartialEq)]
+pub(super) enum Zone<'a> {
+    /// Seconds east of UTC.
+    Offset(i32),
+    /// An IANA zone name like `America/Los_Angeles`.
+    Named(&'a str),
+}
+
+/// Parse a date like `2022-11-28`.  Years may have more than four digits, or be negative.
+pub(super) fn parse_date(value: &str) -> Option<DateParts> {
+    let (negative, unsigned) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let mut parts = unsigned.splitn(3, '-');
+    let year: i32 = parse_digits(parts.next()?)?;
+    let month = parse_digits(parts.next()?)?;
+    let day = parse_digits(parts.next()?)?;
+    Some(DateParts {
+        year: if negative { -year } else { year },
+        month,
+        day,
+    })
+}
+
+/// Parse a time like `03:52:42` or `03:52:42.123456789012`.
+pub(super) fn parse_time(value: &str) -> Option<TimeParts> {
+    let (hms, fraction) = match value.split_once('.') {
+        Some((hms, fraction)) => (hms, Some(fraction)),
+        None => (value, None),
+    };
+    let mut parts = hms.splitn(3, ':');
+    let hour = parse_digits(parts.next()?)?;
+    let minute = parse_digits(parts.next()?)?;
+    let second = parts.next().map_or(Some(0), parse_digits)?;
+    let nanosecond = match fraction {
+        Some(fraction) => {
+            if fraction.is_empty() || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            let nanos: String = fraction
+                .chars()
+                .chain("000000000".chars())
+                .take(9)
+                .collect();
+            nanos.parse().ok()?
+        }
+        None => 0,
+    };
+    Some(TimeParts {
+        hour,
+        minute,
+        second,
+        nanosecond,
+    })
+}
+
+/// Parse a timestamp like `2022-11-28 03:52:42.123`.
+pub(super) fn parse_timestamp(value: &str) -> Option<(DateParts, TimeParts)> {
+    let (date, time) = value.split_once(' ')?;
+    Some((parse_date(date)?, parse_time(time)?))
+}
+
+/// Parse a timestamp with a zone, like `2022-11-28 03:52:42.123 UTC`.
+pub(super) fn parse_timestamp_with_time_zone(
+    value: &str,
+) -> Option<(DateParts, TimeParts, Zone<'_>)> {
+    let (timestamp, zone) = value.rsplit_once(' ')?;
+    let (date, time) = parse_timestamp(timestamp)?;
+    Some((date, time, parse_zone(zone)?))
+}
+
+/// Parse a time with an offset, like `03:52:42.123+05:30`.
+pub(super) fn parse_time_with_time_zone(value: &str) -> Option<(TimeParts, i32)> {
+    let split = value.rfind(['+', '-'])?;
+    let (time, offset) = value.split_at(split);
+    Some((parse_time(time)?, parse_offset(offset)?))
+}
+
+/// Parse a zone, which is either an offset like `+05:30` or a zone name.
+pub(super) fn parse_zone(zone: &str) -> Option<Zone<'_>> {
+    if zone.eq_ignore_ascii_case("UTC") || zone == "Z" {
+        Some(Zone::Offset(0))
+    } else if zone.starts_with(['+', '-']) {
+        parse_offset(zone).map(Zone::Offset)
+    } else if zone.is_empty() {
+        None
+    } else {
+        Some(Zone::Named(zone))
+    }
+}
+
+/// Parse an offset like `+05:30` or `-08:00` into seconds east of UTC.
+pub(super) fn parse_offset(offset: &str) -> Option<i32> {
+    let (sign, rest) = match offset.strip_prefix('+') {
+        Some(rest) => (1, rest),
+        None => (-1, offset.strip_prefix('-')?),
+    };
+    let (hours, minutes) = rest.split_once(':').unwrap_or((rest, "00"));
+    let hours: i32 = parse_digits(hours)?;
+    let minutes: i32 = parse_digits(minutes)?;
+    Some(sign * (hours * 3600 + minutes * 60))
+}
+
+fn parse_digits<N: std::str::FromStr>(digits: &str) -> Option<N> {
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+pub(super) fn invalid(type_name: &str, value: &str) -> String {
+    format!("invalid {type_name} '{value}'")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_date() {
+        let date = |year, month, day| DateParts { year, month, day };
+        assert_eq!(parse_date("2022-11-28"), Some(date(2022, 11, 28)));
+        assert_eq!(parse_date("12345-01-02"), Some(date(12345, 1, 2)));
+        assert_eq!(parse_date("-0044-03-15"), Some(date(-44, 3, 15)));
+        assert_eq!(parse_date("2022-11"), None);
+        assert_eq!(parse_date("2022-1a-01"), None);
+    }
+
+    #[test]
+    fn test_parse_time() {
+        let time = |hour, minute, second, nanosecond| TimeParts {
+            hour,
+            minute,
+            second,
+            nanosecond,
+        };
+        assert_eq!(parse_time("03:52:42"), Some(time(3, 52, 42, 0)));
+        assert_eq!(parse_time("03:52:42.1"), Some(time(3, 52, 42, 100_000_000)));
+        assert_eq!(
+            parse_time("03:52:42.123456789012"),
+            Some(time(3, 52, 42, 123_456_789))
+        );
+        assert_eq!(parse_time("03:52:42."), None);
+        assert_eq!(parse_time("03:52:42.12a"), None);
+        assert_eq!(parse_time("03"), None);
+    }
+
+    #[test]
+    fn test_parse_zones() {
+        assert_eq!(parse_zone("UTC"), Some(Zone::Offset(0)));
+        assert_eq!(parse_zone("+05:30"), Some(Zone::Offset(19800)));
+        assert_eq!(parse_zone("-08:00"), Some(Zone::Offset(-28800)));
+        assert_eq!(
+            parse_zone("America/Los_Angeles"),
+            Some(Zone::Named("America/Los_Angeles"))
+        );
+        assert_eq!(parse_zone("+5x"), None);
+
+        let (_, time, zone) =
+            parse_timestamp_with_time_zone("2022-11-28 03:52:42.123 America/Los_Angeles").unwrap();
+        assert_eq!(time.nanosecond, 123_000_000);
+        assert_eq!(zone, Zone::Named("America/Los_Angeles"));
+        assert_eq!(
+            parse_time_with_time_zone("03:52:42.123-08:00").map(|(_, offset)| offset),
+            Some(-28800)
+        );
+        assert_eq!(parse_time_with_time_zone("03:52:42.123"), None);
+    }
+}
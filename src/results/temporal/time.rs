@@ -0,0 +1,224 @@
+//! Adapters for `time` types.  Named zones are resolved with `time-tz`.
+
+use super::parse::{self, invalid, DateParts, TimeParts, Zone};
+use crate::results::adapter::string_adapter;
+use ::time::{Date, Month, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset};
+use time_tz::{timezones, PrimitiveDateTimeExt, TimeZone, Tz};
+
+string_adapter!(
+    /// A `date` column as a `Date`.
+    date,
+    Date,
+    parse_date
+);
+
+string_adapter!(
+    /// A `time(p)` column as a `Time`.
+    time,
+    Time,
+    parse_time
+);
+
+string_adapter!(
+    /// A `time(p) with time zone` column as a `Time` and its offset.
+    time_with_time_zone,
+    (Time, UtcOffset),
+    parse_time_with_time_zone
+);
+
+string_adapter!(
+    /// A `timestamp(p)` column as a `PrimitiveDateTime`.
+    timestamp,
+    PrimitiveDateTime,
+    parse_timestamp
+);
+
+string_adapter!(
+    /// A `timestamp(p) with time zone` column as an `OffsetDateTime` with the value's offset.
+    timestamp_with_time_zone,
+    OffsetDateTime,
+    parse_timestamp_with_time_zone
+);
+
+string_adapter!(
+    /// A `timestamp(p)` column as an `OffsetDateTime` in the session time zone, which
+    /// must be set with `Headers::set_timezone`.
+    timestamp_in_session_zone,
+    OffsetDateTime,
+    parse_timestamp_in_session_zone
+);
+
+fn parse_date(value: &str) -> Result<Date, String> {
+    parse::parse_date(value)
+        .and_then(to_date)
+        .ok_or_else(|| invalid("date", value))
+}
+
+fn parse_time(value: &str) -> Result<Time, String> {
+    parse::parse_time(value)
+        .and_then(to_time)
+        .ok_or_else(|| invalid("time", value))
+}
+
+fn parse_time_with_time_zone(value: &str) -> Result<(Time, UtcOffset), String> {
+    parse::parse_time_with_time_zone(value)
+        .and_then(|(time, offset)| Some((to_time(time)?, to_offset(offset)?)))
+        .ok_or_else(|| invalid("time with time zone", value))
+}
+
+fn parse_timestamp(value: &str) -> Result<PrimitiveDateTime, String> {
+    parse::parse_timestamp(value)
+        .and_then(|(date, time)| to_datetime(date, time))
+        .ok_or_else(|| invalid("timestamp", value))
+}
+
+fn parse_timestamp_with_time_zone(value: &str) -> Result<OffsetDateTime, String> {
+    parse::parse_timestamp_with_time_zone(value)
+        .and_then(|(date, time, zone)| in_zone(to_datetime(date, time)?, &zone))
+        .ok_or_else(|| invalid("timestamp with time zone", value))
+}
+
+fn parse_timestamp_in_session_zone(value: &str) -> Result<OffsetDateTime, String> {
+    let session_zone = super::session_time_zone()?;
+    let zone = parse::parse_zone(&session_zone)
+        .ok_or_else(|| format!("invalid session time zone '{session_zone}'"))?;
+    let datetime = parse_timestamp(value)?;
+    in_zone(datetime, &zone)
+        .ok_or_else(|| format!("timestamp '{value}' does not exist in time zone '{session_zone}'"))
+}
+
+fn to_date(date: DateParts) -> Option<Date> {
+    Date::from_calendar_date(date.year, Month::try_from(date.month).ok()?, date.day).ok()
+}
+
+fn to_time(time: TimeParts) -> Option<Time> {
+    Time::from_hms_nano(time.hour, time.minute, time.second, time.nanosecond).ok()
+}
+
+fn to_datetime(date: DateParts, time: TimeParts) -> Option<PrimitiveDateTime> {
+    Some(PrimitiveDateTime::new(to_date(date)?, to_time(time)?))
+}
+
+fn to_offset(seconds: i32) -> Option<UtcOffset> {
+    UtcOffset::from_whole_seconds(seconds).ok()
+}
+
+/// The instant of the local `datetime` in `zone`.  If the local time is ambiguous
+/// (when clocks go back), this is the earlier instant, as on the server.
+fn in_zone(datetime: PrimitiveDateTime, zone: &Zone) -> Option<OffsetDateTime> {
+    match zone {
+        Zone::Offset(seconds) => Some(datetime.assume_offset(to_offset(*seconds)?)),
+        Zone::Named(name) => datetime.assume_timezone(find_zone(name)?).take_first(),
+    }
+}
+
+/// Zone names from the server are canonical, but the session time zone is lowercased
+/// in the request headers.
+fn find_zone(name: &str) -> Option<&'static Tz> {
+    timezones::get_by_name(name)
+        .or_else(|| timezones::iter().find(|tz| tz.name().eq_ignore_ascii_case(name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::results::temporal::with_session_time_zone;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[test]
+    fn test_dates_and_times() {
+        #[derive(Deserialize)]
+        struct Row {
+            #[serde(with = "date")]
+            date: Date,
+            #[serde(with = "time")]
+            time: Time,
+            #[serde(with = "time::option")]
+            null_time: Option<Time>,
+            #[serde(with = "time_with_time_zone")]
+            time_tz: (Time, UtcOffset),
+        }
+
+        let row: Row = serde_json::from_value(json!([
+            "2022-11-28",
+            "03:52:42.123456789012",
+            null,
+            "03:52:42.123-08:00"
+        ]))
+        .unwrap();
+        assert_eq!(
+            row.date,
+            Date::from_calendar_date(2022, Month::November, 28).unwrap()
+        );
+        assert_eq!(
+            row.time,
+            Time::from_hms_nano(3, 52, 42, 123_456_789).unwrap()
+        );
+        assert_eq!(row.null_time, None);
+        assert_eq!(
+            row.time_tz,
+            (
+                Time::from_hms_milli(3, 52, 42, 123).unwrap(),
+                UtcOffset::from_hms(-8, 0, 0).unwrap()
+            )
+        );
+
+        let result =
+            serde_json::from_value::<Row>(json!(["2022-13-01", "00:00:00", null, "00:00:00Z"]));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_timestamps() {
+        #[derive(Deserialize)]
+        struct Row {
+            #[serde(with = "timestamp")]
+            ts: PrimitiveDateTime,
+            #[serde(with = "timestamp_with_time_zone")]
+            named: OffsetDateTime,
+            #[serde(with = "timestamp_with_time_zone")]
+            offset: OffsetDateTime,
+            #[serde(with = "timestamp_with_time_zone::option")]
+            null_ts: Option<OffsetDateTime>,
+        }
+
+        let row: Row = serde_json::from_value(json!([
+            "2022-11-28 03:52:42.123456789012",
+            "2022-11-28 03:52:42.123 America/Los_Angeles",
+            "2022-07-01 12:00:00 -03:30",
+            null,
+        ]))
+        .unwrap();
+        assert_eq!(row.ts.nanosecond(), 123_456_789);
+        assert_eq!(row.named.offset(), UtcOffset::from_hms(-8, 0, 0).unwrap());
+        assert_eq!(row.named.unix_timestamp(), 1669636362);
+        assert_eq!(
+            row.offset.offset(),
+            UtcOffset::from_hms(-3, -30, 0).unwrap()
+        );
+        assert_eq!(row.null_ts, None);
+
+        let summer = timestamp_with_time_zone::deserialize(json!(
+            "2022-07-01 00:00:00.000 America/Los_Angeles"
+        ))
+        .unwrap();
+        assert_eq!(summer.offset(), UtcOffset::from_hms(-7, 0, 0).unwrap());
+
+        let result = timestamp_with_time_zone::deserialize(json!("2022-07-01 00:00:00 Mars/Base"));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_timestamp_in_session_zone() {
+        let value = json!("2022-07-01 12:00:00.000");
+        assert!(timestamp_in_session_zone::deserialize(value.clone()).is_err());
+
+        let datetime = with_session_time_zone(Some("america/los_angeles"), || {
+            timestamp_in_session_zone::deserialize(value)
+        })
+        .unwrap();
+        assert_eq!(datetime.offset(), UtcOffset::from_hms(-7, 0, 0).unwrap());
+        assert_eq!(datetime.hour(), 12);
+    }
+}
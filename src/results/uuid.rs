@@ -0,0 +1,41 @@
+//! Serde helpers for `uuid` columns, behind the `uuid` feature.
+
+use super::adapter::{deserialize_option_with, deserialize_with};
+use ::uuid::Uuid;
+use serde::Deserializer;
+
+/// Deserialize a `Uuid` from a `uuid` column.
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Uuid, D::Error> {
+    deserialize_with(deserializer, parse_uuid)
+}
+
+pub mod option {
+    use super::*;
+
+    /// Deserialize an `Option<Uuid>` from a nullable `uuid` column.
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Uuid>, D::Error> {
+        deserialize_option_with(deserializer, parse_uuid)
+    }
+}
+
+fn parse_uuid(value: &str) -> Result<Uuid, String> {
+    Uuid::parse_str(value).map_err(|err| format!("invalid uuid '{value}': {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_uuid() {
+        assert_eq!(
+            deserialize(json!("12151fd2-7586-11e9-8f9e-2a86e4085a59")).unwrap(),
+            Uuid::from_u128(0x12151fd2_7586_11e9_8f9e_2a86e4085a59)
+        );
+        assert!(deserialize(json!("12151fd2")).is_err());
+        assert_eq!(option::deserialize(json!(null)).unwrap(), None);
+    }
+}
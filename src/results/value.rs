@@ -0,0 +1,374 @@
+use super::{Column, PrestoType};
+use crate::PrestinoError;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde_json::Value as JsonValue;
+
+/// A dynamically typed value, decoded from the JSON sent by the server according to
+/// its column's `PrestoType`.  This is for when the schema of the results is not
+/// known at compile time; otherwise, deserializing rows into a tuple or struct is simpler.
+///
+/// Decimals and temporal types are kept as the strings the server sends, so no
+/// precision is lost.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Boolean(bool),
+    TinyInt(i8),
+    SmallInt(i16),
+    Integer(i32),
+    BigInt(i64),
+    Real(f32),
+    Double(f64),
+    Decimal(String),
+    Varchar(String),
+    Char(String),
+    Varbinary(Vec<u8>),
+    Json(JsonValue),
+    Date(String),
+    Time(String),
+    TimeWithTimeZone(String),
+    Timestamp(String),
+    TimestampWithTimeZone(String),
+    IntervalDayToSecond(String),
+    IntervalYearToMonth(String),
+    Uuid(String),
+    IpAddress(String),
+    Array(Vec<Value>),
+    /// Map entries, in no particular order.  Currently they are sorted by the text
+    /// of their keys as the server sent them, so the key 10 comes before 2.
+    Map(Vec<(Value, Value)>),
+    /// Row fields, in the order of the row type's fields.
+    Row(Vec<Value>),
+    /// A value of a type that prestino doesn't know about, as sent by the server.
+    Other(JsonValue),
+}
+
+impl Value {
+    /// Decode a row (a JSON array with one value per column) using the column types.
+    pub fn decode_row(columns: &[Column], row: JsonValue) -> Result<Vec<Value>, PrestinoError> {
+        let JsonValue::Array(values) = row else {
+            return Err(decode_error(&row, "row"));
+        };
+        if values.len() != columns.len() {
+            return Err(PrestinoError::ValueDecodeError(format!(
+                "row has {} values but there are {} columns",
+                values.len(),
+                columns.len()
+            )));
+        }
+        columns
+            .iter()
+            .zip(values)
+            .map(|(column, value)| Value::decode(column.presto_type(), value))
+            .collect()
+    }
+
+    /// Decode a JSON value as sent by the server for the given type.
+    pub fn decode(presto_type: &PrestoType, json: JsonValue) -> Result<Value, PrestinoError> {
+        if json.is_null() {
+            return Ok(Value::Null);
+        }
+        let value = match presto_type {
+            PrestoType::Boolean => Value::Boolean(
+                json.as_bool()
+                    .ok_or_else(|| decode_error(&json, "boolean"))?,
+            ),
+            PrestoType::TinyInt => Value::TinyInt(decode_integer(&json, "tinyint")?),
+            PrestoType::SmallInt => Value::SmallInt(decode_integer(&json, "smallint")?),
+            PrestoType::Integer => Value::Integer(decode_integer(&json, "integer")?),
+            PrestoType::BigInt => Value::BigInt(decode_integer(&json, "bigint")?),
+            PrestoType::Real => Value::Real(decode_float(&json, "real")? as f32),
+            PrestoType::Double => Value::Double(decode_float(&json, "double")?),
+            PrestoType::Decimal { .. } => match json {
+                JsonValue::String(s) => Value::Decimal(s),
+                JsonValue::Number(n) => Value::Decimal(n.to_string()),
+                _ => return Err(decode_error(&json, "decimal")),
+            },
+            PrestoType::Varchar(_) => Value::Varchar(decode_string(json, "varchar")?),
+            PrestoType::Char(_) => Value::Char(decode_string(json, "char")?),
+            PrestoType::Varbinary => {
+                let encoded = decode_string(json, "varbinary")?;
+                Value::Varbinary(BASE64.decode(&encoded).map_err(|err| {
+                    PrestinoError::ValueDecodeError(format!(
+                        "invalid base64 varbinary {encoded}: {err}"
+                    ))
+                })?)
+            }
+            PrestoType::Json => {
+                let text = decode_string(json, "json")?;
+                Value::Json(serde_json::from_str(&text)?)
+            }
+            PrestoType::Date => Value::Date(decode_string(json, "date")?),
+            PrestoType::Time(_) => Value::Time(decode_string(json, "time")?),
+            PrestoType::TimeWithTimeZone(_) => {
+                Value::TimeWithTimeZone(decode_string(json, "time with time zone")?)
+            }
+            PrestoType::Timestamp(_) => Value::Timestamp(decode_string(json, "timestamp")?),
+            PrestoType::TimestampWithTimeZone(_) => {
+                Value::TimestampWithTimeZone(decode_string(json, "timestamp with time zone")?)
+            }
+            PrestoType::IntervalDayToSecond => {
+                Value::IntervalDayToSecond(decode_string(json, "interval day to second")?)
+            }
+            PrestoType::IntervalYearToMonth => {
+                Value::IntervalYearToMonth(decode_string(json, "interval year to month")?)
+            }
+            PrestoType::Uuid => Value::Uuid(decode_string(json, "uuid")?),
+            PrestoType::IpAddress => Value::IpAddress(decode_string(json, "ipaddress")?),
+            PrestoType::Array(element_type) => {
+                let JsonValue::Array(elements) = json else {
+                    return Err(decode_error(&json, "array"));
+                };
+                Value::Array(
+                    elements
+                        .into_iter()
+                        .map(|element| Value::decode(element_type, element))
+                        .collect::<Result<_, _>>()?,
+                )
+            }
+            PrestoType::Map(key_type, value_type) => {
+                let JsonValue::Object(entries) = json else {
+                    return Err(decode_error(&json, "map"));
+                };
+                Value::Map(
+                    entries
+                        .into_iter()
+                        .map(|(key, value)| {
+                            Ok((
+                                Value::decode(key_type, decode_map_key(key_type, key)?)?,
+                                Value::decode(value_type, value)?,
+                            ))
+                        })
+                        .collect::<Result<_, PrestinoError>>()?,
+                )
+            }
+            PrestoType::Row(fields) => {
+                let JsonValue::Array(values) = json else {
+                    return Err(decode_error(&json, "row"));
+                };
+                if values.len() != fields.len() {
+                    return Err(PrestinoError::ValueDecodeError(format!(
+                        "row value has {} fields but its type has {}",
+                        values.len(),
+                        fields.len()
+                    )));
+                }
+                Value::Row(
+                    fields
+                        .iter()
+                        .zip(values)
+                        .map(|(field, value)| Value::decode(&field.field_type, value))
+                        .collect::<Result<_, _>>()?,
+                )
+            }
+            PrestoType::Unknown | PrestoType::Other(_) => Value::Other(json),
+        };
+        Ok(value)
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+}
+
+fn decode_error(json: &JsonValue, type_name: &str) -> PrestinoError {
+    PrestinoError::ValueDecodeError(format!("could not decode {json} as {type_name}"))
+}
+
+fn decode_string(json: JsonValue, type_name: &str) -> Result<String, PrestinoError> {
+    match json {
+        JsonValue::String(s) => Ok(s),
+        _ => Err(decode_error(&json, type_name)),
+    }
+}
+
+fn decode_integer<I: TryFrom<i64>>(json: &JsonValue, type_name: &str) -> Result<I, PrestinoError> {
+    json.as_i64()
+        .and_then(|i| I::try_from(i).ok())
+        .ok_or_else(|| decode_error(json, type_name))
+}
+
+/// Floats are sent as JSON numbers, except for NaN and the infinities, which are strings.
+fn decode_float(json: &JsonValue, type_name: &str) -> Result<f64, PrestinoError> {
+    match json {
+        JsonValue::Number(n) => n.as_f64(),
+        JsonValue::String(s) => match s.as_str() {
+            "NaN" => Some(f64::NAN),
+            "Infinity" => Some(f64::INFINITY),
+            "-Infinity" => Some(f64::NEG_INFINITY),
+            _ => None,
+        },
+        _ => None,
+    }
+    .ok_or_else(|| decode_error(json, type_name))
+}
+
+/// Map keys are always sent as JSON strings, so the keys of numeric and boolean types
+/// need to be parsed back into JSON.  Decimal keys are kept as strings, like decimal
+/// values, so their exact digits and scale are kept, and keys of other types (like
+/// dates) are strings anyway.
+fn decode_map_key(key_type: &PrestoType, key: String) -> Result<JsonValue, PrestinoError> {
+    let parsed = match key_type {
+        PrestoType::TinyInt | PrestoType::SmallInt | PrestoType::Integer | PrestoType::BigInt => {
+            key.parse::<i64>().ok().map(JsonValue::from)
+        }
+        PrestoType::Real | PrestoType::Double => match key.as_str() {
+            "NaN" | "Infinity" | "-Infinity" => Some(JsonValue::String(key.clone())),
+            _ => key
+                .parse::<f64>()
+                .ok()
+                .and_then(serde_json::Number::from_f64)
+                .map(JsonValue::Number),
+        },
+        PrestoType::Boolean => key.parse::<bool>().ok().map(JsonValue::Bool),
+        _ => return Ok(JsonValue::String(key)),
+    };
+    parsed.ok_or_else(|| {
+        PrestinoError::ValueDecodeError(format!("could not decode map key {key:?} as {key_type:?}"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn decode(type_name: &str, json: JsonValue) -> Value {
+        Value::decode(&PrestoType::parse(type_name), json).unwrap()
+    }
+
+    #[test]
+    fn test_decode_primitives() {
+        assert_eq!(decode("boolean", json!(true)), Value::Boolean(true));
+        assert_eq!(decode("tinyint", json!(-3)), Value::TinyInt(-3));
+        assert_eq!(decode("integer", json!(null)), Value::Null);
+        assert_eq!(decode("bigint", json!(i64::MAX)), Value::BigInt(i64::MAX));
+        assert_eq!(decode("double", json!(1.5)), Value::Double(1.5));
+        assert_eq!(
+            decode("real", json!("-Infinity")),
+            Value::Real(f32::NEG_INFINITY)
+        );
+        assert!(matches!(decode("double", json!("NaN")), Value::Double(f) if f.is_nan()));
+        assert_eq!(
+            decode(
+                "decimal(38,10)",
+                json!("1234567890123456789012345678.0123456789")
+            ),
+            Value::Decimal("1234567890123456789012345678.0123456789".to_owned())
+        );
+        assert_eq!(
+            decode("varchar(3)", json!("abc")),
+            Value::Varchar("abc".to_owned())
+        );
+        assert_eq!(
+            decode("varbinary", json!("AAH/")),
+            Value::Varbinary(vec![0, 1, 255])
+        );
+        assert_eq!(
+            decode("json", json!("{\"a\":[1]}")),
+            Value::Json(json!({"a": [1]}))
+        );
+        assert_eq!(
+            decode(
+                "timestamp(3) with time zone",
+                json!("2022-11-28 03:52:42.123 UTC")
+            ),
+            Value::TimestampWithTimeZone("2022-11-28 03:52:42.123 UTC".to_owned())
+        );
+        assert_eq!(
+            decode("HyperLogLog", json!("AgwB")),
+            Value::Other(json!("AgwB"))
+        );
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let result = Value::decode(&PrestoType::TinyInt, json!(300));
+        assert!(matches!(result, Err(PrestinoError::ValueDecodeError(_))));
+        let result = Value::decode(&PrestoType::Boolean, json!("true"));
+        assert!(matches!(result, Err(PrestinoError::ValueDecodeError(_))));
+        let result = Value::decode(&PrestoType::parse("array(bigint)"), json!([1, "a"]));
+        assert!(matches!(result, Err(PrestinoError::ValueDecodeError(_))));
+        for (type_name, key) in [("map(bigint, bigint)", "1.5"), ("map(double, bigint)", "x")] {
+            let result = Value::decode(&PrestoType::parse(type_name), json!({ key: 1 }));
+            assert!(matches!(result, Err(PrestinoError::ValueDecodeError(_))));
+        }
+    }
+
+    #[test]
+    fn test_decode_nested() {
+        assert_eq!(
+            decode("array(map(bigint, date))", json!([{"1": "2022-01-01"}, {}])),
+            Value::Array(vec![
+                Value::Map(vec![(
+                    Value::BigInt(1),
+                    Value::Date("2022-01-01".to_owned())
+                )]),
+                Value::Map(vec![]),
+            ])
+        );
+        assert_eq!(
+            decode("map(date, varchar)", json!({"2022-01-01": "a"})),
+            Value::Map(vec![(
+                Value::Date("2022-01-01".to_owned()),
+                Value::Varchar("a".to_owned())
+            )])
+        );
+        assert_eq!(
+            decode(
+                "map(decimal(10,2), double)",
+                json!({"1.10": 1.5, "-2.00": "Infinity"})
+            ),
+            Value::Map(vec![
+                (
+                    Value::Decimal("-2.00".to_owned()),
+                    Value::Double(f64::INFINITY)
+                ),
+                (Value::Decimal("1.10".to_owned()), Value::Double(1.5)),
+            ])
+        );
+        assert_eq!(
+            decode(
+                "map(double, boolean)",
+                json!({"1.5": true, "-Infinity": false})
+            ),
+            Value::Map(vec![
+                (Value::Double(f64::NEG_INFINITY), Value::Boolean(false)),
+                (Value::Double(1.5), Value::Boolean(true)),
+            ])
+        );
+        assert_eq!(
+            decode("map(integer, varchar)", json!({"2": "b", "10": "a"})),
+            Value::Map(vec![
+                (Value::Integer(10), Value::Varchar("a".to_owned())),
+                (Value::Integer(2), Value::Varchar("b".to_owned())),
+            ])
+        );
+        assert_eq!(
+            decode("map(boolean, tinyint)", json!({"true": 1})),
+            Value::Map(vec![(Value::Boolean(true), Value::TinyInt(1))])
+        );
+        assert_eq!(
+            decode("row(a bigint, b array(boolean))", json!([1, [true, null]])),
+            Value::Row(vec![
+                Value::BigInt(1),
+                Value::Array(vec![Value::Boolean(true), Value::Null])
+            ])
+        );
+    }
+
+    #[test]
+    fn test_decode_row() {
+        let columns: Vec<Column> = serde_json::from_value(json!([
+            {"name": "a", "type": "integer"},
+            {"name": "b", "type": "varchar"},
+        ]))
+        .unwrap();
+        assert_eq!(
+            Value::decode_row(&columns, json!([1, "x"])).unwrap(),
+            vec![Value::Integer(1), Value::Varchar("x".to_owned())]
+        );
+        assert!(Value::decode_row(&columns, json!([1])).is_err());
+    }
+}
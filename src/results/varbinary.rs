@@ -0,0 +1,43 @@
+//! Serde helpers for `varbinary` columns, which the server sends base64 encoded.
+
+use super::adapter::{deserialize_option_with, deserialize_with};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::Deserializer;
+
+/// Deserialize the bytes of a `varbinary` column.
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    deserialize_with(deserializer, parse_varbinary)
+}
+
+pub mod option {
+    use super::*;
+
+    /// Deserialize the bytes of a nullable `varbinary` column.
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Vec<u8>>, D::Error> {
+        deserialize_option_with(deserializer, parse_varbinary)
+    }
+}
+
+fn parse_varbinary(value: &str) -> Result<Vec<u8>, String> {
+    BASE64
+        .decode(value)
+        .map_err(|err| format!("invalid base64 varbinary '{value}': {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_varbinary() {
+        assert_eq!(deserialize(json!("AAH/")).unwrap(), vec![0, 1, 255]);
+        assert_eq!(deserialize(json!("")).unwrap(), Vec::<u8>::new());
+        assert!(deserialize(json!("AAH")).is_err());
+        assert!(deserialize(json!([0, 1])).is_err());
+        assert_eq!(option::deserialize(json!(null)).unwrap(), None);
+    }
+}
@@ -0,0 +1,181 @@
+use crate::PrestinoError;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// When to retry a failed request for the next results of a statement, or to cancel
+/// one.  These requests are idempotent, so a transient failure (a dropped connection,
+/// or a gateway or overloaded server) needn't fail a statement that may have been
+/// running for hours.
+///
+/// A failed request is retried up to `max_attempts` times in all, waiting after each
+/// failure for a backoff that starts at `initial_backoff` and is multiplied by
+/// `multiplier` on each retry, up to `max_backoff`.  Each wait is randomly shortened
+/// by up to the `jitter` fraction, so clients that failed together don't retry
+/// together.
+#[derive(Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: f64,
+    jitter: f64,
+    is_retryable: Arc<dyn Fn(&PrestinoError) -> bool + Send + Sync>,
+}
+
+impl RetryPolicy {
+    /// Make each request just once.
+    pub fn never() -> Self {
+        Self::default().max_attempts(1)
+    }
+
+    /// The number of times to try a request, including the first.  At least 1.
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// The wait after the first failure, and the most to wait after any failure.
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max.max(initial);
+        self
+    }
+
+    /// How much longer to wait after each successive failure.  At least 1.
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier.max(1.0);
+        self
+    }
+
+    /// The largest fraction (from 0 to 1) by which to randomly shorten each wait.
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Which errors to retry, in place of `RetryPolicy::is_transient`.
+    pub fn retry_if(
+        mut self,
+        is_retryable: impl Fn(&PrestinoError) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.is_retryable = Arc::new(is_retryable);
+        self
+    }
+
+    /// Whether the error is likely to be transient: a connection failure or timeout,
+    /// or a 502, 503 or 504 response.  This is what is retried by default.
+    pub fn is_transient(err: &PrestinoError) -> bool {
+        match err {
+            PrestinoError::HttpError(err) => {
+                err.is_connect() || err.is_timeout() || err.is_request() || err.is_body()
+            }
+            PrestinoError::StatusCodeError(code, _) => (502..=504).contains(code),
+            _ => false,
+        }
+    }
+
+    /// How long to wait before retrying after `err`, which failed attempt number
+    /// `attempt` (counting from 1), or `None` if the request shouldn't be retried.
+    pub(crate) fn retry_delay(&self, err: &PrestinoError, attempt: u32) -> Option<Duration> {
+        if attempt >= self.max_attempts || !(self.is_retryable)(err) {
+            return None;
+        }
+        let backoff = self.initial_backoff.as_secs_f64()
+            * self.multiplier.powi(attempt.saturating_sub(1) as i32);
+        let backoff = backoff.min(self.max_backoff.as_secs_f64());
+        Some(Duration::from_secs_f64(
+            backoff * (1.0 - self.jitter * random_fraction()),
+        ))
+    }
+}
+
+impl Default for RetryPolicy {
+    /// Try each request up to 5 times, waiting 100ms after the first failure (as the
+    /// protocol asks after a 503), doubling up to 10s, with 20% jitter.
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+            multiplier: 2.0,
+            jitter: 0.2,
+            is_retryable: Arc::new(Self::is_transient),
+        }
+    }
+}
+
+impl fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("initial_backoff", &self.initial_backoff)
+            .field("max_backoff", &self.max_backoff)
+            .field("multiplier", &self.multiplier)
+            .field("jitter", &self.jitter)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A random number in [0, 1), from the randomly seeded std hasher, which is plenty
+/// for jitter.
+fn random_fraction() -> f64 {
+    let random = RandomState::new().build_hasher().finish();
+    (random >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unavailable() -> PrestinoError {
+        PrestinoError::StatusCodeError(503, String::new())
+    }
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy::default()
+            .max_attempts(5)
+            .backoff(Duration::from_millis(100), Duration::from_millis(300))
+            .jitter(0.0);
+        let delays: Vec<_> = (1..=5)
+            .map(|attempt| policy.retry_delay(&unavailable(), attempt))
+            .collect();
+        assert_eq!(
+            delays,
+            vec![
+                Some(Duration::from_millis(100)),
+                Some(Duration::from_millis(200)),
+                Some(Duration::from_millis(300)),
+                Some(Duration::from_millis(300)),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_jitter() {
+        let policy = RetryPolicy::default().jitter(0.5);
+        for _ in 0..100 {
+            let delay = policy.retry_delay(&unavailable(), 1).unwrap();
+            assert!(delay > Duration::from_millis(50) && delay <= Duration::from_millis(100));
+        }
+    }
+
+    #[test]
+    fn test_retryable() {
+        let policy = RetryPolicy::default();
+        assert!(policy.retry_delay(&unavailable(), 1).is_some());
+        let not_found = PrestinoError::StatusCodeError(404, String::new());
+        assert!(policy.retry_delay(&not_found, 1).is_none());
+        assert!(RetryPolicy::never()
+            .retry_delay(&unavailable(), 1)
+            .is_none());
+
+        let policy = policy.retry_if(|err| matches!(err, PrestinoError::StatusCodeError(404, _)));
+        assert!(policy.retry_delay(&not_found, 1).is_some());
+        assert!(policy.retry_delay(&unavailable(), 1).is_none());
+    }
+}
@@ -0,0 +1,364 @@
+use crate::results::Column;
+use crate::value_deserializer::RowDeserializer;
+use crate::{PrestinoError, RowDecodeError};
+use serde::de::{self, DeserializeOwned, Deserializer, Visitor};
+use serde_json::Value;
+use std::cell::Cell;
+use std::marker::PhantomData;
+
+/// How the elements of a row are matched to the fields of the row type.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub(crate) enum RowMode {
+    /// Rows are deserialized from JSON arrays, so fields are matched by position.
+    #[default]
+    Positional,
+    /// Rows are deserialized from JSON objects keyed by column name.
+    ByName,
+}
+
+/// Decodes the JSON rows of a statement's responses into `T`.
+#[derive(Debug)]
+pub(crate) struct RowDecoder<T> {
+    mode: RowMode,
+    /// For `ByName`, the struct fields of `T` that have no column, once checked.
+    missing_fields: Option<Vec<String>>,
+    /// The index in the statement's results of the next row to decode.
+    next_row_index: usize,
+    _row_type: PhantomData<fn() -> T>,
+}
+
+impl<T: DeserializeOwned> RowDecoder<T> {
+    pub fn new(mode: RowMode) -> Self {
+        Self {
+            mode,
+            missing_fields: None,
+            next_row_index: 0,
+            _row_type: PhantomData,
+        }
+    }
+
+    pub fn mode(&self) -> RowMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: RowMode) {
+        self.mode = mode;
+        self.missing_fields = None;
+    }
+
+    /// Decode the rows of a page of results.  An error decoding a row is returned as
+    /// a `PrestinoError::RowDecodeError`.
+    pub fn decode_rows(
+        &mut self,
+        columns: Option<&[Column]>,
+        rows: Vec<Value>,
+        page: &Page,
+    ) -> Result<Vec<T>, PrestinoError> {
+        if rows.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.check_columns(columns)?;
+        rows.into_iter()
+            .map(|row| {
+                self.decode(columns, row, page)
+                    .map_err(PrestinoError::RowDecodeError)
+            })
+            .collect()
+    }
+
+    /// Decode each of the rows of a page of results separately, so an error decoding
+    /// one row doesn't affect the others.  Only an error in the columns themselves
+    /// fails the whole page.
+    #[allow(clippy::result_large_err)]
+    pub fn decode_rows_lenient(
+        &mut self,
+        columns: Option<&[Column]>,
+        rows: Vec<Value>,
+        page: &Page,
+    ) -> Result<Vec<Result<T, RowDecodeError>>, PrestinoError> {
+        if rows.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.check_columns(columns)?;
+        Ok(rows
+            .into_iter()
+            .map(|row| self.decode(columns, row, page).map_err(|err| *err))
+            .collect())
+    }
+
+    /// For `ByName`, check that the columns match the fields of `T`, once.
+    fn check_columns(&mut self, columns: Option<&[Column]>) -> Result<(), PrestinoError> {
+        if self.mode != RowMode::ByName || self.missing_fields.is_some() {
+            return Ok(());
+        }
+        let Some(columns) = columns else {
+            return Err(PrestinoError::ColumnMismatchError(
+                "received data before the result columns".to_owned(),
+            ));
+        };
+        self.missing_fields = Some(check_fields::<T>(columns)?);
+        Ok(())
+    }
+
+    /// Decode the next row, which is in `page`.
+    fn decode(
+        &mut self,
+        columns: Option<&[Column]>,
+        row: Value,
+        page: &Page,
+    ) -> Result<T, Box<RowDecodeError>> {
+        let row_index = self.next_row_index;
+        self.next_row_index += 1;
+        let current_column = Cell::new(None);
+        self.deserialize(columns, &row, &current_column)
+            .map_err(|source| {
+                Box::new(RowDecodeError {
+                    query_id: page.query_id.to_owned(),
+                    page: page.token.map(ToOwned::to_owned),
+                    row_index,
+                    column: current_column
+                        .get()
+                        .and_then(|index| columns?.get(index))
+                        .cloned(),
+                    row,
+                    source,
+                })
+            })
+    }
+
+    fn deserialize(
+        &self,
+        columns: Option<&[Column]>,
+        row: &Value,
+        current_column: &Cell<Option<usize>>,
+    ) -> Result<T, PrestinoError> {
+        let Value::Array(values) = row else {
+            return Err(PrestinoError::ColumnMismatchError(format!(
+                "expected row to be an array, got {row}"
+            )));
+        };
+        if self.mode == RowMode::Positional {
+            return Ok(T::deserialize(RowDeserializer::positional(
+                values,
+                current_column,
+            ))?);
+        }
+
+        // check_columns has ensured these are set.
+        let (Some(columns), Some(missing_fields)) = (columns, &self.missing_fields) else {
+            unreachable!("columns are checked before decoding rows by name");
+        };
+        if values.len() != columns.len() {
+            return Err(PrestinoError::ColumnMismatchError(format!(
+                "row has {} values but there are {} columns",
+                values.len(),
+                columns.len()
+            )));
+        }
+        T::deserialize(RowDeserializer::by_name(values, columns, current_column)).map_err(|err| {
+            if missing_fields.is_empty() {
+                err.into()
+            } else {
+                PrestinoError::ColumnMismatchError(format!(
+                    "no result column for field(s) {}: {err}",
+                    missing_fields.join(", ")
+                ))
+            }
+        })
+    }
+}
+
+/// Where a page of rows came from, for the context of decoding errors.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Page<'a> {
+    pub query_id: &'a str,
+    /// The last segment of the page's URI, or `None` for the response to the statement.
+    pub token: Option<&'a str>,
+}
+
+impl<'a> Page<'a> {
+    pub fn new(query_id: &'a str, uri: Option<&'a str>) -> Self {
+        Self {
+            query_id,
+            token: uri.and_then(|uri| uri.trim_end_matches('/').rsplit('/').next()),
+        }
+    }
+}
+
+/// Check the columns against the fields of `T`, if it is a struct, returning the
+/// fields that have no column.  Those are allowed (e.g. for `Option` or `#[serde(default)]`
+/// fields), but columns that have no field, or duplicate columns, are errors.
+fn check_fields<T: DeserializeOwned>(columns: &[Column]) -> Result<Vec<String>, PrestinoError> {
+    let mut names: Vec<&str> = columns.iter().map(Column::name).collect();
+    names.sort_unstable();
+    if let Some(pair) = names.windows(2).find(|pair| pair[0] == pair[1]) {
+        return Err(PrestinoError::ColumnMismatchError(format!(
+            "duplicate column {}",
+            pair[0]
+        )));
+    }
+
+    let Some(fields) = struct_fields::<T>() else {
+        return Ok(Vec::new());
+    };
+    let extra_columns: Vec<&str> = names
+        .iter()
+        .filter(|name| !fields.contains(name))
+        .copied()
+        .collect();
+    if !extra_columns.is_empty() {
+        return Err(PrestinoError::ColumnMismatchError(format!(
+            "no field for result column(s) {}",
+            extra_columns.join(", ")
+        )));
+    }
+    Ok(fields
+        .iter()
+        .filter(|field| !names.contains(field))
+        .map(|field| field.to_string())
+        .collect())
+}
+
+/// The (serialized) field names of `T`, if it is deserialized as a struct.
+fn struct_fields<T: DeserializeOwned>() -> Option<&'static [&'static str]> {
+    let mut fields = None;
+    let _ = T::deserialize(FieldsDeserializer {
+        fields: &mut fields,
+    });
+    fields
+}
+
+/// A Deserializer that only records the fields requested by `deserialize_struct`.
+struct FieldsDeserializer<'a> {
+    fields: &'a mut Option<&'static [&'static str]>,
+}
+
+impl<'de, 'a> Deserializer<'de> for FieldsDeserializer<'a> {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(de::Error::custom("not a struct"))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.fields = Some(fields);
+        Err(de::Error::custom("not deserializing"))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Row {
+        a: i64,
+        #[serde(rename = "b_str")]
+        b: String,
+        c: Option<bool>,
+    }
+
+    fn make_columns(names: &[&str]) -> Vec<Column> {
+        names
+            .iter()
+            .map(|name| serde_json::from_value(json!({"name": name, "type": "bigint"})).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_struct_fields() {
+        assert_eq!(struct_fields::<Row>(), Some(&["a", "b_str", "c"][..]));
+        assert_eq!(struct_fields::<(i64, String)>(), None);
+        assert_eq!(struct_fields::<Value>(), None);
+    }
+
+    fn decode(
+        decoder: &mut RowDecoder<Row>,
+        columns: &[Column],
+        row: Value,
+    ) -> Result<Row, PrestinoError> {
+        let page = Page::new("query_id", Some("http://localhost/v1/statement/query_id/1"));
+        let mut rows = decoder.decode_rows(Some(columns), vec![row], &page)?;
+        Ok(rows.remove(0))
+    }
+
+    #[test]
+    fn test_decode_by_name() {
+        let mut decoder = RowDecoder::<Row>::new(RowMode::ByName);
+        let columns = make_columns(&["c", "b_str", "a"]);
+        let row = decode(&mut decoder, &columns, json!([true, "x", 1])).unwrap();
+        assert_eq!(
+            row,
+            Row {
+                a: 1,
+                b: "x".to_owned(),
+                c: Some(true)
+            }
+        );
+
+        // Option fields may be missing.
+        let mut decoder = RowDecoder::<Row>::new(RowMode::ByName);
+        let columns = make_columns(&["b_str", "a"]);
+        let row = decode(&mut decoder, &columns, json!(["x", 1])).unwrap();
+        assert_eq!(row.c, None);
+    }
+
+    #[test]
+    fn test_decode_by_name_mismatch() {
+        let mut decoder = RowDecoder::<Row>::new(RowMode::ByName);
+        let result = decode(&mut decoder, &make_columns(&["a", "c"]), json!([1, true]));
+        assert!(
+            matches!(&result, Err(PrestinoError::RowDecodeError(err))
+                if matches!(&err.source, PrestinoError::ColumnMismatchError(msg) if msg.contains("b_str"))),
+            "{result:?}"
+        );
+
+        let mut decoder = RowDecoder::<Row>::new(RowMode::ByName);
+        let result = decode(
+            &mut decoder,
+            &make_columns(&["a", "b_str", "d"]),
+            json!([1, "x", 2]),
+        );
+        assert!(
+            matches!(&result, Err(PrestinoError::ColumnMismatchError(msg)) if msg.contains("column(s) d")),
+            "{result:?}"
+        );
+
+        let mut decoder = RowDecoder::<Row>::new(RowMode::ByName);
+        let result = decode(&mut decoder, &make_columns(&["a", "a"]), json!([1, 2]));
+        assert!(
+            matches!(&result, Err(PrestinoError::ColumnMismatchError(msg)) if msg.contains("duplicate")),
+            "{result:?}"
+        );
+    }
+
+    #[test]
+    fn test_decode_error_context() {
+        let mut decoder = RowDecoder::<Row>::new(RowMode::Positional);
+        let columns = make_columns(&["a", "b_str", "c"]);
+        decode(&mut decoder, &columns, json!([1, "x", null])).unwrap();
+        let result = decode(&mut decoder, &columns, json!([2, 3, null]));
+        let Err(PrestinoError::RowDecodeError(err)) = result else {
+            panic!("Expected a RowDecodeError, got {result:?}");
+        };
+        assert_eq!(err.query_id, "query_id");
+        assert_eq!(err.page.as_deref(), Some("1"));
+        assert_eq!(err.row_index, 1);
+        assert_eq!(err.column.as_ref().map(Column::name), Some("b_str"));
+        assert_eq!(err.row, json!([2, 3, null]));
+        assert!(matches!(err.source, PrestinoError::JsonParseError(_)));
+    }
+}
@@ -0,0 +1,62 @@
+//! The async runtime the crate runs on, chosen by cargo feature: `tokio` (the
+//! default) or `async-std`.  If both are enabled, tokio is used.
+
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+#[cfg(not(any(feature = "tokio", feature = "async-std")))]
+compile_error!("prestino needs a runtime: enable the `tokio` or `async-std` feature");
+
+/// Run a future in the background, without waiting for its result.  This is used
+/// to clean up server-side state from `Drop` implementations, so outside of a
+/// runtime it does nothing but log a warning.
+#[cfg(feature = "tokio")]
+pub(crate) fn spawn<F>(future: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) => {
+            handle.spawn(future);
+        }
+        Err(_) => log::warn!("No runtime available to run background request; skipping it"),
+    }
+}
+
+/// Run a future in the background, without waiting for its result.  This is used
+/// to clean up server-side state from `Drop` implementations.
+#[cfg(all(feature = "async-std", not(feature = "tokio")))]
+pub(crate) fn spawn<F>(future: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    async_std::task::spawn(future);
+}
+
+/// Wait for the duration.
+pub(crate) async fn sleep(duration: Duration) {
+    #[cfg(feature = "tokio")]
+    tokio::time::sleep(duration).await;
+    #[cfg(all(feature = "async-std", not(feature = "tokio")))]
+    async_std::task::sleep(duration).await;
+    #[cfg(not(any(feature = "tokio", feature = "async-std")))]
+    unreachable!("no runtime to sleep for {duration:?}");
+}
+
+/// Run a future until the deadline, returning `None` if the deadline passes first.
+pub(crate) async fn run_until<F: Future>(
+    deadline: Option<Instant>,
+    future: F,
+) -> Option<F::Output> {
+    let Some(deadline) = deadline else {
+        return Some(future.await);
+    };
+    #[cfg(feature = "tokio")]
+    return tokio::time::timeout_at(deadline.into(), future).await.ok();
+    #[cfg(all(feature = "async-std", not(feature = "tokio")))]
+    return async_std::future::timeout(deadline.saturating_duration_since(Instant::now()), future)
+        .await
+        .ok();
+    #[cfg(not(any(feature = "tokio", feature = "async-std")))]
+    unreachable!("no runtime to wait until {deadline:?}");
+}
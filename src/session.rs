@@ -0,0 +1,117 @@
+use crate::{
+    Headers, IsolationLevel, PreparedStatement, PrestinoClient, PrestinoError, StatementExecutor,
+    Transaction,
+};
+use serde::de::DeserializeOwned;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// A Session runs statements against the same server-side state, like a session
+/// in the Presto/Trino CLI.  When a statement changes the catalog, schema, session
+/// properties, role or transaction (e.g. `USE tpch.tiny` or `SET SESSION ...`),
+/// the server reports this in the response headers, and the Session applies it
+/// to all subsequent statements.
+///
+/// The state is updated as the statement's responses are received, so a statement's
+/// changes are only guaranteed to be visible once its results have been fully read.
+#[derive(Debug)]
+pub struct Session {
+    client: PrestinoClient,
+    headers: Arc<Mutex<Headers>>,
+}
+
+impl Session {
+    pub(crate) fn new(client: PrestinoClient) -> Self {
+        let headers = client.headers().clone();
+        Self {
+            client,
+            headers: Arc::new(Mutex::new(headers)),
+        }
+    }
+
+    pub fn client(&self) -> &PrestinoClient {
+        &self.client
+    }
+
+    /// A snapshot of the session's current headers.
+    pub fn headers(&self) -> Headers {
+        self.lock_headers().clone()
+    }
+
+    /// Update the session's headers with the values from `headers`.  These will be
+    /// used for all subsequent statements in this session.
+    pub fn update_headers(&self, headers: &Headers) {
+        self.lock_headers().update(headers);
+    }
+
+    pub(crate) fn lock_headers(&self) -> MutexGuard<'_, Headers> {
+        self.headers.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Start a transaction in this session.  Until it is committed or rolled back,
+    /// all statements in this session will run in the transaction.
+    pub async fn begin_transaction(
+        &self,
+        isolation: IsolationLevel,
+        read_only: bool,
+    ) -> Result<Transaction<'_>, PrestinoError> {
+        Transaction::begin(self, isolation, read_only).await
+    }
+
+    /// Prepare a statement with the given name, which can then be executed with
+    /// parameters bound to its `?` placeholders.  The `name` must be an identifier
+    /// (`[A-Za-z_][A-Za-z0-9_]*`), or this returns a `StatementNameError` without
+    /// sending anything.
+    pub async fn prepare(
+        &self,
+        name: &str,
+        statement: &str,
+    ) -> Result<PreparedStatement<'_>, PrestinoError> {
+        PreparedStatement::prepare(self, name, statement).await
+    }
+
+    /// Begin execution of a statement, returning a StatementExecutor to continue execution.
+    pub async fn execute<T: DeserializeOwned>(
+        &self,
+        statement: impl Into<String>,
+    ) -> Result<StatementExecutor<T>, PrestinoError> {
+        let new_headers = self.client.headers().new_with_fork();
+        self.execute_with_headers(statement, &new_headers).await
+    }
+
+    /// Begin execution of a statement, returning a StatementExecutor to continue execution.
+    /// The supplied headers apply only to this statement, and are not kept by the session.
+    pub async fn execute_with_headers<T: DeserializeOwned>(
+        &self,
+        statement: impl Into<String>,
+        headers: &Headers,
+    ) -> Result<StatementExecutor<T>, PrestinoError> {
+        let mut connection_headers = self.headers();
+        connection_headers.update(headers);
+        self.client
+            .start_statement(statement, connection_headers, Some(self.headers.clone()))
+            .await
+    }
+
+    /// A convenience function to retrieve all the rows for the statement into a single Vec.
+    pub async fn execute_collect<T: DeserializeOwned>(
+        &self,
+        statement: impl Into<String>,
+    ) -> Result<Vec<T>, PrestinoError> {
+        let new_headers = self.client.headers().new_with_fork();
+        self.execute_collect_with_headers(statement, &new_headers)
+            .await
+    }
+
+    /// A convenience function to retrieve all the rows for the statement into a single Vec.
+    /// The supplied headers apply only to this statement, and are not kept by the session.
+    pub async fn execute_collect_with_headers<T: DeserializeOwned>(
+        &self,
+        statement: impl Into<String>,
+        headers: &Headers,
+    ) -> Result<Vec<T>, PrestinoError> {
+        self.execute_with_headers::<T>(statement, headers)
+            .await?
+            .collect()
+            .await
+    }
+}
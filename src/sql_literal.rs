@@ -0,0 +1,459 @@
+use crate::PrestinoError;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+
+/// Types that can be written as a SQL literal, for safely including values in statements
+/// (e.g. as the parameters of a prepared statement) instead of formatting them by hand.
+///
+/// `Vec`s are written as `ARRAY[...]` and maps as `MAP(ARRAY[...], ARRAY[...])`.
+/// Byte slices (`[u8]`) are written as `VARBINARY` literals like `X'00ff'`; note that
+/// a `Vec<u8>` is an array of integers, so use `.as_slice()` for binary data.
+pub trait ToSqlLiteral {
+    /// The SQL literal for this value, such as `'it''s'` for the string `it's`.
+    fn to_sql_literal(&self) -> String;
+}
+
+impl<T: ToSqlLiteral + ?Sized> ToSqlLiteral for &T {
+    fn to_sql_literal(&self) -> String {
+        (**self).to_sql_literal()
+    }
+}
+
+impl<T: ToSqlLiteral> ToSqlLiteral for Option<T> {
+    fn to_sql_literal(&self) -> String {
+        match self {
+            Some(value) => value.to_sql_literal(),
+            None => "NULL".to_owned(),
+        }
+    }
+}
+
+impl ToSqlLiteral for bool {
+    fn to_sql_literal(&self) -> String {
+        if *self { "TRUE" } else { "FALSE" }.to_owned()
+    }
+}
+
+macro_rules! impl_integer_literal {
+    ($($t:ty),*) => {
+        $(
+            impl ToSqlLiteral for $t {
+                fn to_sql_literal(&self) -> String {
+                    parenthesize_negative(self.to_string())
+                }
+            }
+        )*
+    };
+}
+
+impl_integer_literal!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+/// Write a negative number in parentheses, since substituted straight after a minus
+/// sign (as in `10-:n`), `-1` would start a `--` comment.
+fn parenthesize_negative(literal: String) -> String {
+    if literal.starts_with('-') {
+        format!("({literal})")
+    } else {
+        literal
+    }
+}
+
+impl ToSqlLiteral for f64 {
+    fn to_sql_literal(&self) -> String {
+        if self.is_nan() {
+            "nan()".to_owned()
+        } else if self.is_infinite() {
+            if *self > 0.0 {
+                "infinity()"
+            } else {
+                "(-infinity())"
+            }
+            .to_owned()
+        } else {
+            // A literal with an exponent is a DOUBLE; without one it would be a DECIMAL.
+            parenthesize_negative(format!("{self:e}"))
+        }
+    }
+}
+
+impl ToSqlLiteral for f32 {
+    fn to_sql_literal(&self) -> String {
+        if self.is_finite() {
+            format!("REAL '{self}'")
+        } else {
+            format!("CAST({} AS REAL)", f64::from(*self).to_sql_literal())
+        }
+    }
+}
+
+impl ToSqlLiteral for str {
+    fn to_sql_literal(&self) -> String {
+        format!("'{}'", self.replace('\'', "''"))
+    }
+}
+
+impl ToSqlLiteral for String {
+    fn to_sql_literal(&self) -> String {
+        self.as_str().to_sql_literal()
+    }
+}
+
+impl ToSqlLiteral for [u8] {
+    fn to_sql_literal(&self) -> String {
+        let mut literal = String::with_capacity(2 * self.len() + 3);
+        literal.push_str("X'");
+        for byte in self {
+            write!(literal, "{byte:02x}").unwrap();
+        }
+        literal.push('\'');
+        literal
+    }
+}
+
+impl<T: ToSqlLiteral> ToSqlLiteral for Vec<T> {
+    fn to_sql_literal(&self) -> String {
+        array_literal(self.iter())
+    }
+}
+
+impl<K: ToSqlLiteral, V: ToSqlLiteral, S> ToSqlLiteral for HashMap<K, V, S> {
+    fn to_sql_literal(&self) -> String {
+        map_literal(self.iter())
+    }
+}
+
+impl<K: ToSqlLiteral, V: ToSqlLiteral> ToSqlLiteral for BTreeMap<K, V> {
+    fn to_sql_literal(&self) -> String {
+        map_literal(self.iter())
+    }
+}
+
+fn array_literal<T: ToSqlLiteral>(values: impl Iterator<Item = T>) -> String {
+    let literals: Vec<String> = values.map(|v| v.to_sql_literal()).collect();
+    format!("ARRAY[{}]", literals.join(", "))
+}
+
+fn map_literal<'a, K, V>(entries: impl Iterator<Item = (&'a K, &'a V)>) -> String
+where
+    K: ToSqlLiteral + 'a,
+    V: ToSqlLiteral + 'a,
+{
+    let (keys, values): (Vec<&K>, Vec<&V>) = entries.unzip();
+    format!(
+        "MAP({}, {})",
+        array_literal(keys.into_iter()),
+        array_literal(values.into_iter())
+    )
+}
+
+#[cfg(feature = "chrono")]
+mod chrono_literals {
+    use super::ToSqlLiteral;
+    use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+    use std::fmt::Display;
+
+    impl ToSqlLiteral for NaiveDate {
+        fn to_sql_literal(&self) -> String {
+            format!("DATE '{}'", self.format("%Y-%m-%d"))
+        }
+    }
+
+    impl ToSqlLiteral for NaiveTime {
+        fn to_sql_literal(&self) -> String {
+            format!("TIME '{}'", self.format("%H:%M:%S%.f"))
+        }
+    }
+
+    impl ToSqlLiteral for NaiveDateTime {
+        fn to_sql_literal(&self) -> String {
+            format!("TIMESTAMP '{}'", self.format("%Y-%m-%d %H:%M:%S%.f"))
+        }
+    }
+
+    impl<Tz: TimeZone> ToSqlLiteral for DateTime<Tz>
+    where
+        Tz::Offset: Display,
+    {
+        fn to_sql_literal(&self) -> String {
+            format!("TIMESTAMP '{}'", self.format("%Y-%m-%d %H:%M:%S%.f %:z"))
+        }
+    }
+}
+
+#[cfg(feature = "rust_decimal")]
+impl ToSqlLiteral for rust_decimal::Decimal {
+    fn to_sql_literal(&self) -> String {
+        format!("DECIMAL '{self}'")
+    }
+}
+
+#[cfg(feature = "bigdecimal")]
+impl ToSqlLiteral for bigdecimal::BigDecimal {
+    fn to_sql_literal(&self) -> String {
+        format!("DECIMAL '{self}'")
+    }
+}
+
+/// Substitute named parameters like `:name` in `template` with the SQL literals of
+/// the corresponding values.  Placeholders inside string literals, quoted identifiers
+/// and comments are left alone.  The [`sql!`](crate::sql) macro is a more convenient
+/// way to call this.
+///
+/// Returns `PrestinoError::MissingParameterError` if a placeholder has no value.
+pub fn format_sql(
+    template: &str,
+    params: &[(&str, &dyn ToSqlLiteral)],
+) -> Result<String, PrestinoError> {
+    let mut statement = String::with_capacity(template.len());
+    let mut chars = template.char_indices().peekable();
+    while let Some((idx, c)) = chars.next() {
+        statement.push(c);
+        match c {
+            '\'' | '"' => {
+                // Copy the quoted string/identifier.  A doubled quote is an escaped quote,
+                // which this handles as two adjacent quoted sections.
+                for (_, next) in chars.by_ref() {
+                    statement.push(next);
+                    if next == c {
+                        break;
+                    }
+                }
+            }
+            '-' if matches!(chars.peek(), Some((_, '-'))) => {
+                for (_, next) in chars.by_ref() {
+                    statement.push(next);
+                    if next == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if matches!(chars.peek(), Some((_, '*'))) => {
+                statement.push(chars.next().unwrap().1);
+                let mut prev = ' ';
+                for (_, next) in chars.by_ref() {
+                    statement.push(next);
+                    if prev == '*' && next == '/' {
+                        break;
+                    }
+                    prev = next;
+                }
+            }
+            ':' if matches!(chars.peek(), Some((_, n)) if n.is_ascii_alphabetic() || *n == '_') => {
+                statement.pop();
+                let start = idx + 1;
+                let mut end = start;
+                while let Some(&(next_idx, next)) = chars.peek() {
+                    if !(next.is_ascii_alphanumeric() || next == '_') {
+                        break;
+                    }
+                    end = next_idx + next.len_utf8();
+                    chars.next();
+                }
+                let name = &template[start..end];
+                let Some((_, value)) = params.iter().find(|(n, _)| *n == name) else {
+                    return Err(PrestinoError::MissingParameterError(name.to_owned()));
+                };
+                statement.push_str(&value.to_sql_literal());
+            }
+            _ => (),
+        }
+    }
+    Ok(statement)
+}
+
+/// Build a statement from a template with `:name` placeholders, substituting the SQL
+/// literals of the named values.  This evaluates to a `Result<String, PrestinoError>`;
+/// see [`format_sql`](crate::format_sql).
+///
+/// ```
+/// let name = "O'Brien";
+/// let statement = prestino::sql!(
+///     "SELECT * FROM people WHERE name = :name AND age > :age",
+///     name = name,
+///     age = 30,
+/// )
+/// .unwrap();
+/// assert_eq!(statement, "SELECT * FROM people WHERE name = 'O''Brien' AND age > 30");
+/// ```
+#[macro_export]
+macro_rules! sql {
+    ($template:expr $(, $name:ident = $value:expr)* $(,)?) => {
+        $crate::format_sql(
+            $template,
+            &[$((stringify!($name), &$value as &dyn $crate::ToSqlLiteral)),*],
+        )
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_primitives() {
+        assert_eq!(true.to_sql_literal(), "TRUE");
+        assert_eq!(false.to_sql_literal(), "FALSE");
+        assert_eq!((-12i8).to_sql_literal(), "(-12)");
+        assert_eq!(i64::MAX.to_sql_literal(), "9223372036854775807");
+        assert_eq!(u32::MAX.to_sql_literal(), "4294967295");
+        assert_eq!(u64::MAX.to_sql_literal(), "18446744073709551615");
+        assert_eq!(
+            i128::MIN.to_sql_literal(),
+            "(-170141183460469231731687303715884105728)"
+        );
+        assert_eq!(3usize.to_sql_literal(), "3");
+        assert_eq!(1.5f64.to_sql_literal(), "1.5e0");
+        assert_eq!((-0.001f64).to_sql_literal(), "(-1e-3)");
+        assert_eq!(f64::NAN.to_sql_literal(), "nan()");
+        assert_eq!(f64::NEG_INFINITY.to_sql_literal(), "(-infinity())");
+        assert_eq!(1.5f32.to_sql_literal(), "REAL '1.5'");
+        assert_eq!(f32::INFINITY.to_sql_literal(), "CAST(infinity() AS REAL)");
+    }
+
+    #[test]
+    fn test_strings() {
+        assert_eq!("abc".to_sql_literal(), "'abc'");
+        assert_eq!("it's".to_sql_literal(), "'it''s'");
+        assert_eq!(
+            "'; DROP TABLE t; --".to_owned().to_sql_literal(),
+            "'''; DROP TABLE t; --'"
+        );
+        assert_eq!("".to_sql_literal(), "''");
+    }
+
+    #[test]
+    fn test_option() {
+        assert_eq!(Some(3).to_sql_literal(), "3");
+        assert_eq!(None::<&str>.to_sql_literal(), "NULL");
+        assert_eq!(Some("a").to_sql_literal(), "'a'");
+    }
+
+    #[test]
+    fn test_collections() {
+        assert_eq!(vec![1, 2, 3].to_sql_literal(), "ARRAY[1, 2, 3]");
+        assert_eq!(Vec::<i32>::new().to_sql_literal(), "ARRAY[]");
+        assert_eq!(
+            vec![vec!["a"], vec![]].to_sql_literal(),
+            "ARRAY[ARRAY['a'], ARRAY[]]"
+        );
+        assert_eq!(vec![Some(1), None].to_sql_literal(), "ARRAY[1, NULL]");
+
+        let map = BTreeMap::from([("a", 1), ("b'", 2)]);
+        assert_eq!(map.to_sql_literal(), "MAP(ARRAY['a', 'b'''], ARRAY[1, 2])");
+        let map: HashMap<i32, Vec<bool>> = HashMap::from([(1, vec![true])]);
+        assert_eq!(map.to_sql_literal(), "MAP(ARRAY[1], ARRAY[ARRAY[TRUE]])");
+    }
+
+    #[test]
+    fn test_bytes() {
+        assert_eq!(b"\x00\x0f\xff".as_slice().to_sql_literal(), "X'000fff'");
+        assert_eq!([].as_slice().to_sql_literal(), "X''");
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_chrono() {
+        use chrono::{FixedOffset, NaiveDate, TimeZone, Utc};
+
+        let date = NaiveDate::from_ymd_opt(2022, 11, 28).unwrap();
+        assert_eq!(date.to_sql_literal(), "DATE '2022-11-28'");
+        let datetime = date.and_hms_milli_opt(3, 52, 42, 123).unwrap();
+        assert_eq!(
+            datetime.to_sql_literal(),
+            "TIMESTAMP '2022-11-28 03:52:42.123'"
+        );
+        assert_eq!(datetime.time().to_sql_literal(), "TIME '03:52:42.123'");
+        assert_eq!(
+            Utc.from_utc_datetime(&datetime).to_sql_literal(),
+            "TIMESTAMP '2022-11-28 03:52:42.123 +00:00'"
+        );
+        let offset = FixedOffset::west_opt(8 * 3600).unwrap();
+        assert_eq!(
+            offset.from_utc_datetime(&datetime).to_sql_literal(),
+            "TIMESTAMP '2022-11-27 19:52:42.123 -08:00'"
+        );
+    }
+
+    #[cfg(feature = "rust_decimal")]
+    #[test]
+    fn test_rust_decimal() {
+        use std::str::FromStr;
+        let decimal = rust_decimal::Decimal::from_str("-12.340").unwrap();
+        assert_eq!(decimal.to_sql_literal(), "DECIMAL '-12.340'");
+    }
+
+    #[cfg(feature = "bigdecimal")]
+    #[test]
+    fn test_bigdecimal() {
+        use std::str::FromStr;
+        let decimal =
+            bigdecimal::BigDecimal::from_str("9999999999999999999999999999.9999999999").unwrap();
+        assert_eq!(
+            decimal.to_sql_literal(),
+            "DECIMAL '9999999999999999999999999999.9999999999'"
+        );
+    }
+
+    #[test]
+    fn test_format_sql() {
+        let statement = format_sql(
+            "SELECT * FROM t WHERE a = :a AND b IN (:b_list) AND c = :a",
+            &[("a", &"x"), ("b_list", &3)],
+        )
+        .unwrap();
+        assert_eq!(
+            statement,
+            "SELECT * FROM t WHERE a = 'x' AND b IN (3) AND c = 'x'"
+        );
+    }
+
+    #[test]
+    fn test_format_sql_negative_after_minus() {
+        let statement = format_sql(
+            "SELECT 10-:i, 10-:f, 10-:inf\nFROM t",
+            &[("i", &-1), ("f", &-0.5), ("inf", &f64::NEG_INFINITY)],
+        )
+        .unwrap();
+        assert_eq!(
+            statement,
+            "SELECT 10-(-1), 10-(-5e-1), 10-(-infinity())\nFROM t"
+        );
+    }
+
+    #[test]
+    fn test_format_sql_skips_quotes_and_comments() {
+        let statement = format_sql(
+            "SELECT ':a', \":a\", 'it'':a' -- :a\n, :a /* :a */, TIME '12:30'",
+            &[("a", &1)],
+        )
+        .unwrap();
+        assert_eq!(
+            statement,
+            "SELECT ':a', \":a\", 'it'':a' -- :a\n, 1 /* :a */, TIME '12:30'"
+        );
+    }
+
+    #[test]
+    fn test_format_sql_missing_parameter() {
+        let result = format_sql("SELECT :a, :b", &[("a", &1)]);
+        assert!(matches!(
+            result,
+            Err(PrestinoError::MissingParameterError(name)) if name == "b"
+        ));
+    }
+
+    #[test]
+    fn test_sql_macro() {
+        let values = vec!["x".to_owned(), "y'".to_owned()];
+        let statement = crate::sql!(
+            "SELECT * FROM t WHERE contains(:values, name) AND id = :id",
+            values = values,
+            id = 7i64,
+        )
+        .unwrap();
+        assert_eq!(
+            statement,
+            "SELECT * FROM t WHERE contains(ARRAY['x', 'y'''], name) AND id = 7"
+        );
+    }
+}
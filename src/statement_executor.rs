@@ -1,28 +1,122 @@
 use crate::client_connection::ClientConnection;
-use crate::results::{Column, QueryResults, QueryStats};
-use crate::PrestinoError;
+use crate::results::{self, temporal, Column, QueryResults, QueryStats};
+use crate::row_decoder::{Page, RowDecoder, RowMode};
+use crate::runtime;
+use crate::{PollBackoff, PrestinoError, RowDecodeError};
 use async_stream::try_stream;
-use futures::Stream;
+use futures::{Stream, TryStreamExt};
 use futures_util::pin_mut;
+use log::{debug, warn};
 use serde::de::DeserializeOwned;
+use serde_json::Value;
 use std::time::{Duration, Instant};
 
+/// How long a statement may run, in total and without making progress.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Timeouts {
+    pub total: Option<Duration>,
+    pub no_progress: Option<Duration>,
+}
+
 pub struct StatementExecutor<T: DeserializeOwned> {
     id: String,
     connection: ClientConnection,
-    results: QueryResults<T>,
+    results: QueryResults<Value>,
+    columns: Option<Vec<Column>>,
+    decoder: RowDecoder<T>,
+    /// The URI the current results were fetched from, if not the statement's response.
+    page_uri: Option<String>,
+    /// The next uri when polling failed.  Iteration ends, but the statement may
+    /// still be running, so this is kept to cancel it.
+    failed_uri: Option<String>,
     next_run_time: Instant,
+    poll_backoff: PollBackoff,
+    /// The backoff delay before the next poll, which is zero after a poll that
+    /// returned rows.
+    poll_delay: Duration,
+    cancel_on_drop: bool,
+    timeouts: Timeouts,
+    started: Instant,
+    last_progress: Instant,
 }
 
 impl<T: DeserializeOwned> StatementExecutor<T> {
-    pub(crate) fn new(id: String, connection: ClientConnection, results: QueryResults<T>) -> Self {
+    pub(crate) fn new(
+        id: String,
+        connection: ClientConnection,
+        mut results: QueryResults<Value>,
+    ) -> Self {
         Self {
             id,
             connection,
+            columns: results.columns.take(),
             results,
+            decoder: RowDecoder::new(RowMode::Positional),
+            page_uri: None,
+            failed_uri: None,
             next_run_time: Instant::now(),
+            poll_backoff: PollBackoff::default(),
+            poll_delay: Duration::ZERO,
+            cancel_on_drop: true,
+            timeouts: Timeouts::default(),
+            started: Instant::now(),
+            last_progress: Instant::now(),
         }
     }
+
+    pub(crate) fn with_timeouts(mut self, timeouts: Timeouts, started: Instant) -> Self {
+        self.timeouts = timeouts;
+        self.started = started;
+        self
+    }
+
+    pub(crate) fn with_poll_backoff(mut self, poll_backoff: PollBackoff) -> Self {
+        self.poll_backoff = poll_backoff;
+        self
+    }
+
+    /// Time out the statement if it hasn't finished this long after it was submitted,
+    /// overriding the client's timeout.  The statement is cancelled, and the next
+    /// response is `PrestinoError::Timeout`.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeouts.total = Some(timeout);
+        self
+    }
+
+    /// Time out the statement if it goes this long without making progress (that is,
+    /// without returning rows or advancing its state, splits or processed rows),
+    /// overriding the client's progress timeout.
+    pub fn progress_timeout(mut self, timeout: Duration) -> Self {
+        self.timeouts.no_progress = Some(timeout);
+        self
+    }
+
+    /// Deserialize each row from an object keyed by the column names, instead of
+    /// from an array of the column values.  This lets the fields of a struct row type
+    /// be matched to columns by name rather than by position.  A column without a
+    /// matching field is an error, as is a field without a matching column (unless
+    /// the field is an `Option` or has a `#[serde(default)]`).
+    pub fn deserialize_by_name(mut self) -> Self {
+        self.decoder.set_mode(RowMode::ByName);
+        self
+    }
+
+    pub fn is_deserialized_by_name(&self) -> bool {
+        self.decoder.mode() == RowMode::ByName
+    }
+
+    /// Whether to cancel the statement if this executor (or a stream made from it) is
+    /// dropped before the statement finishes.  This is on by default, so that a
+    /// statement doesn't keep running on the server after its results are abandoned;
+    /// the cancellation is sent in the background.
+    pub fn cancel_on_drop(mut self, cancel_on_drop: bool) -> Self {
+        self.cancel_on_drop = cancel_on_drop;
+        self
+    }
+
+    pub fn is_cancelled_on_drop(&self) -> bool {
+        self.cancel_on_drop
+    }
     pub fn id(&self) -> &str {
         &self.id
     }
@@ -32,26 +126,45 @@ impl<T: DeserializeOwned> StatementExecutor<T> {
     }
 
     pub fn columns(&self) -> Option<&[Column]> {
-        self.results.columns.as_deref()
+        self.columns.as_deref()
     }
 
     pub fn stats(&self) -> &QueryStats {
         &self.results.stats
     }
 
-    /// Cancel execution of this statement.  If the query is already finished,
-    /// return PrestinoError::QueryFinishedError with the query id.
+    /// Cancel execution of this statement, retrying failures as the client's retry
+    /// policy allows.  If the query is already finished, return
+    /// PrestinoError::QueryFinishedError with the query id.
     pub async fn cancel(mut self) -> Result<(), PrestinoError> {
-        let Some(next_uri) = self.results.next_uri.take() else {
+        let Some(next_uri) = self.take_cancel_uri() else {
             return Err(PrestinoError::QueryFinishedError(self.id().to_owned()));
         };
-
-        // TODO: If this is an HTTP error, we should probably try again, or at least
-        // allow the caller to try again.
         self.connection.cancel(&next_uri).await
     }
 
     pub async fn next_response(&mut self) -> Option<Result<Vec<T>, PrestinoError>> {
+        let rows = match self.next_page().await? {
+            Ok(rows) => rows,
+            Err(err) => return Some(Err(err)),
+        };
+        Some(self.decode_rows(rows))
+    }
+
+    /// Like `next_response`, but decode each row separately, so that a row that can't
+    /// be decoded is returned as an `Err` without affecting the rest of the rows.
+    pub async fn next_response_lenient(
+        &mut self,
+    ) -> Option<Result<Vec<Result<T, RowDecodeError>>, PrestinoError>> {
+        let rows = match self.next_page().await? {
+            Ok(rows) => rows,
+            Err(err) => return Some(Err(err)),
+        };
+        Some(self.decode_rows_lenient(rows))
+    }
+
+    /// Fetch the next page of rows, as raw JSON.
+    async fn next_page(&mut self) -> Option<Result<Vec<Value>, PrestinoError>> {
         // Clear out any data that we've saved.
         if let Some(err) = self.results.error.take() {
             return Some(Err(err.into()));
@@ -59,45 +172,107 @@ impl<T: DeserializeOwned> StatementExecutor<T> {
             return Some(Ok(rows));
         }
 
+        // If there is no next_uri, we have finished iteration.  It stays set until the
+        // next results arrive, so the statement can be cancelled if dropped meanwhile.
+        let next_uri = self.results.next_uri.clone()?;
+        let deadline = self.deadline();
         if let Some(delta) = self.next_run_time.checked_duration_since(Instant::now()) {
             // We still need to wait before we can call again
-            async_std::task::sleep(delta).await;
+            let sleep = runtime::sleep(delta);
+            if runtime::run_until(deadline, sleep).await.is_none() {
+                return Some(Err(self.time_out().await));
+            }
         }
 
-        // If there is no next_uri, we have finished iteration.
-        let next_uri = self.results.next_uri.take()?;
-        self.results = match self.connection.get_next_results(&next_uri).await {
-            Err(PrestinoError::StatusCodeError(503, _)) => {
-                // Server is overloaded and needs 100ms:
-                // https://trino.io/docs/current/develop/client-protocol.html#overview-of-query-processing
-                self.bump_next_run_time();
-                self.results.next_uri = Some(next_uri);
-                return Some(Ok(Vec::new()));
+        let response = self.connection.get_next_results(&next_uri);
+        let Some(response) = runtime::run_until(deadline, response).await else {
+            return Some(Err(self.time_out().await));
+        };
+        let previous_stats = self.results.stats.clone();
+        self.results = match response {
+            Err(err) => {
+                self.failed_uri = self.results.next_uri.take();
+                return Some(Err(err));
             }
-            Err(err) => return Some(Err(err)),
             Ok(results) => results,
         };
+        self.page_uri = Some(next_uri);
+        if made_progress(&previous_stats, &self.results) {
+            self.last_progress = Instant::now();
+        }
+        if let Some(columns) = self.results.columns.take() {
+            self.columns = Some(columns);
+        }
 
         if let Some(err) = self.results.error.take() {
             return Some(Err(err.into()));
         }
-        let rows = match self.results.data.take() {
-            Some(r) => {
-                if r.is_empty() {
-                    self.bump_next_run_time();
-                }
-                r
-            }
-            None => {
-                self.bump_next_run_time();
-                Vec::new()
-            }
-        };
+        let rows = self.results.data.take().unwrap_or_default();
+        self.schedule_next_poll(rows.is_empty());
         Some(Ok(rows))
     }
 
-    fn bump_next_run_time(&mut self) {
-        self.next_run_time = Instant::now() + Duration::from_millis(100);
+    fn decode_rows(&mut self, rows: Vec<Value>) -> Result<Vec<T>, PrestinoError> {
+        let time_zone = self.connection.headers.get_timezone();
+        let page = Page::new(&self.id, self.page_uri.as_deref());
+        temporal::with_session_time_zone(time_zone, || {
+            self.decoder
+                .decode_rows(self.columns.as_deref(), rows, &page)
+        })
+    }
+
+    fn decode_rows_lenient(
+        &mut self,
+        rows: Vec<Value>,
+    ) -> Result<Vec<Result<T, RowDecodeError>>, PrestinoError> {
+        let time_zone = self.connection.headers.get_timezone();
+        let page = Page::new(&self.id, self.page_uri.as_deref());
+        temporal::with_session_time_zone(time_zone, || {
+            self.decoder
+                .decode_rows_lenient(self.columns.as_deref(), rows, &page)
+        })
+    }
+
+    /// When the statement times out, if it has a timeout.
+    fn deadline(&self) -> Option<Instant> {
+        let total = self.timeouts.total.map(|timeout| self.started + timeout);
+        let no_progress = self
+            .timeouts
+            .no_progress
+            .map(|timeout| self.last_progress + timeout);
+        total.into_iter().chain(no_progress).min()
+    }
+
+    /// Cancel the statement because it timed out, returning the error for the timeout.
+    async fn time_out(&mut self) -> PrestinoError {
+        debug!("Statement {} timed out", self.id);
+        if let Some(next_uri) = self.results.next_uri.take() {
+            if let Err(err) = self.connection.cancel(&next_uri).await {
+                warn!("Failed to cancel timed out statement {}: {err}", self.id);
+            }
+        }
+        PrestinoError::Timeout(self.id.clone(), Box::new(self.results.stats.clone()))
+    }
+
+    /// The uri to cancel the statement with, if it may still be running.
+    fn take_cancel_uri(&mut self) -> Option<String> {
+        self.results
+            .next_uri
+            .take()
+            .or_else(|| self.failed_uri.take())
+    }
+
+    /// Back off before the next poll if this one was empty, or poll again right away.
+    fn schedule_next_poll(&mut self, empty: bool) {
+        self.poll_delay = if empty {
+            self.poll_backoff.next_delay(self.poll_delay)
+        } else {
+            Duration::ZERO
+        };
+        let wait = self
+            .poll_backoff
+            .wait(self.poll_delay, self.results.stats.queued);
+        self.next_run_time = Instant::now() + wait;
     }
 
     pub fn responses(mut self) -> impl Stream<Item = Result<(Vec<T>, QueryStats), PrestinoError>> {
@@ -121,6 +296,17 @@ impl<T: DeserializeOwned> StatementExecutor<T> {
         }
     }
 
+    /// Retrieve all the remaining rows into a single Vec.
+    pub(crate) async fn collect(self) -> Result<Vec<T>, PrestinoError> {
+        let mut rows: Vec<T> = Vec::new();
+        let stream = self.batches();
+        pin_mut!(stream);
+        while let Some(batch) = stream.try_next().await? {
+            rows.extend(batch);
+        }
+        Ok(rows)
+    }
+
     pub fn rows(self) -> impl Stream<Item = Result<T, PrestinoError>> {
         try_stream! {
             let batches = self.batches();
@@ -132,4 +318,77 @@ impl<T: DeserializeOwned> StatementExecutor<T> {
             }
         }
     }
+
+    /// Stream the rows, decoding each separately so that a row that can't be decoded
+    /// doesn't end the stream.  Such a row is yielded as an inner `Err` with the row
+    /// and its position in the results, so it can be skipped or set aside, and the
+    /// stream continues with the next row.  Errors fetching the results (or a
+    /// mismatch between the columns and a struct row type when deserializing by name)
+    /// still end the stream, as the outer `Err`.
+    pub fn lenient_rows(
+        mut self,
+    ) -> impl Stream<Item = Result<Result<T, RowDecodeError>, PrestinoError>> {
+        try_stream! {
+            while let Some(response) = self.next_response_lenient().await {
+                for row in response? {
+                    yield row;
+                }
+            }
+        }
+    }
+}
+
+impl StatementExecutor<Value> {
+    /// Stream the rows as dynamically typed `results::Value`s, decoded according
+    /// to the column types.
+    pub fn values(mut self) -> impl Stream<Item = Result<Vec<results::Value>, PrestinoError>> {
+        try_stream! {
+            while let Some(response) = self.next_response().await {
+                let rows = response?;
+                if rows.is_empty() {
+                    continue;
+                }
+                let Some(columns) = self.columns() else {
+                    Err(PrestinoError::ValueDecodeError(
+                        "received data before the result columns".to_owned(),
+                    ))?;
+                    return;
+                };
+                for row in rows {
+                    yield results::Value::decode_row(columns, row)?;
+                }
+            }
+        }
+    }
+}
+
+/// Whether the statement made progress between its previous stats and these results.
+fn made_progress(previous: &QueryStats, results: &QueryResults<Value>) -> bool {
+    let stats = &results.stats;
+    results.data.as_ref().is_some_and(|rows| !rows.is_empty())
+        || results.error.is_some()
+        || results.next_uri.is_none()
+        || stats.state != previous.state
+        || stats.completed_splits != previous.completed_splits
+        || stats.processed_rows != previous.processed_rows
+        || stats.processed_bytes != previous.processed_bytes
+}
+
+impl<T: DeserializeOwned> Drop for StatementExecutor<T> {
+    fn drop(&mut self) {
+        if !self.cancel_on_drop {
+            return;
+        }
+        let Some(next_uri) = self.take_cancel_uri() else {
+            return;
+        };
+        debug!("Cancelling dropped statement {}", self.id);
+        let mut connection = self.connection.clone();
+        let id = self.id.clone();
+        crate::runtime::spawn(async move {
+            if let Err(err) = connection.cancel(&next_uri).await {
+                warn!("Failed to cancel statement {id}: {err}");
+            }
+        });
+    }
 }
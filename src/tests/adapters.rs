@@ -0,0 +1,142 @@
+use super::finished_response;
+use crate::results::{interval, ipaddress, json, varbinary};
+use crate::PrestinoClient;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::net::{IpAddr, Ipv4Addr};
+use std::time::Duration;
+use test_log::test;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Row {
+    #[serde(with = "interval::day_to_second")]
+    day_to_second: Duration,
+    #[serde(with = "interval::year_to_month")]
+    year_to_month: i32,
+    #[serde(with = "ipaddress")]
+    ip: IpAddr,
+    #[serde(with = "varbinary")]
+    bytes: Vec<u8>,
+    #[serde(with = "json")]
+    json: Value,
+    #[serde(with = "json::option")]
+    null_json: Option<Value>,
+}
+
+#[test(tokio::test)]
+async fn test_adapters() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/statement"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string(finished_response(
+                &mock_server.uri(),
+                Some(json!([
+                    [
+                        "1 00:00:01.500",
+                        "-1-6",
+                        "10.0.0.1",
+                        "AAH/",
+                        "{\"a\":[1,2]}",
+                        null
+                    ],
+                    ["0 00:00:00.000", "0-0", "::ffff:10.0.0.2", "", "null", "[]"],
+                ])),
+            )),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = PrestinoClient::trino(mock_server.uri()).user("me");
+    let rows: Vec<Row> = client.execute_collect("SELECT * FROM t").await.unwrap();
+    assert_eq!(
+        rows,
+        vec![
+            Row {
+                day_to_second: Duration::from_millis(86_401_500),
+                year_to_month: -18,
+                ip: IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)),
+                bytes: vec![0, 1, 255],
+                json: json!({"a": [1, 2]}),
+                null_json: None,
+            },
+            Row {
+                day_to_second: Duration::ZERO,
+                year_to_month: 0,
+                ip: IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2)),
+                bytes: vec![],
+                json: Value::Null,
+                null_json: Some(json!([])),
+            },
+        ]
+    );
+}
+
+#[cfg(feature = "uuid")]
+#[test(tokio::test)]
+async fn test_uuid_adapter() {
+    use crate::results::uuid as uuid_adapter;
+    use uuid::Uuid;
+
+    #[derive(Debug, Deserialize)]
+    struct UuidRow {
+        #[serde(with = "uuid_adapter")]
+        id: Uuid,
+        #[serde(with = "uuid_adapter::option")]
+        parent: Option<Uuid>,
+    }
+
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/statement"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string(finished_response(
+                &mock_server.uri(),
+                Some(json!([["12151fd2-7586-11e9-8f9e-2a86e4085a59", null]])),
+            )),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = PrestinoClient::trino(mock_server.uri()).user("me");
+    let rows: Vec<UuidRow> = client.execute_collect("SELECT * FROM t").await.unwrap();
+    assert_eq!(
+        rows[0].id,
+        Uuid::parse_str("12151fd2-7586-11e9-8f9e-2a86e4085a59").unwrap()
+    );
+    assert_eq!(rows[0].parent, None);
+}
+
+#[test(tokio::test)]
+async fn test_negative_interval_is_an_error() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/statement"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string(finished_response(
+                &mock_server.uri(),
+                Some(json!([["-1 00:00:00.000"]])),
+            )),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    #[derive(Debug, Deserialize)]
+    struct IntervalRow {
+        #[allow(dead_code)]
+        #[serde(with = "interval::day_to_second")]
+        interval: Duration,
+    }
+    let client = PrestinoClient::trino(mock_server.uri()).user("me");
+    let result = client
+        .execute_collect::<IntervalRow>("SELECT * FROM t")
+        .await;
+    assert!(result.is_err());
+}
@@ -0,0 +1,244 @@
+use super::finished_response;
+use super::response_chain::ResponseChain;
+use crate::{Authentication, ExternalAuthentication, PrestinoClient, PrestinoError};
+use serde_json::json;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use test_log::test;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Mock a statement with one page of data, whose requests must all carry the
+/// `authorization` header (so that a request without it gets a 404).
+async fn mock_authenticated_statement(mock_server: &MockServer, authorization: &str) {
+    let response_strs = ResponseChain::make_response_set(&[("a", "bigint")], &[json!([[1]])]);
+    let response_ref: Vec<&str> = response_strs.iter().map(AsRef::as_ref).collect();
+    let chain = ResponseChain::new(&response_ref, mock_server.uri());
+    Mock::given(method("POST"))
+        .and(path("/v1/statement"))
+        .and(header("authorization", authorization))
+        .respond_with(ResponseTemplate::new(200).set_body_string(&chain.first_response))
+        .expect(1)
+        .mount(mock_server)
+        .await;
+    for (next_uri, body) in chain.next_uris.iter().zip(&chain.next_responses) {
+        Mock::given(method("GET"))
+            .and(path(next_uri))
+            .and(header("authorization", authorization))
+            .respond_with(ResponseTemplate::new(200).set_body_string(body))
+            .mount(mock_server)
+            .await;
+    }
+    Mock::given(method("DELETE"))
+        .and(header("authorization", authorization))
+        .respond_with(ResponseTemplate::new(204))
+        .mount(mock_server)
+        .await;
+}
+
+#[test(tokio::test)]
+async fn test_basic_authentication() {
+    let mock_server = MockServer::start().await;
+    mock_authenticated_statement(&mock_server, "Basic dXNlcjpzZWNyZXQ=").await;
+
+    let client = PrestinoClient::trino(mock_server.uri())
+        .user("me")
+        .authentication(Authentication::basic("user", "secret"))
+        .allow_insecure_authentication(true);
+    let rows: Vec<(i64,)> = client.execute_collect("test").await.unwrap();
+    assert_eq!(rows, vec![(1,)]);
+    mock_server.verify().await;
+}
+
+#[test(tokio::test)]
+async fn test_bearer_authentication_on_cancel() {
+    let mock_server = MockServer::start().await;
+    mock_authenticated_statement(&mock_server, "Bearer a.b.c").await;
+
+    let client = PrestinoClient::trino(mock_server.uri())
+        .user("me")
+        .authentication(Authentication::bearer("a.b.c"))
+        .allow_insecure_authentication(true);
+    let executor = client.execute::<(i64,)>("test").await.unwrap();
+    executor.cancel().await.unwrap();
+}
+
+#[test(tokio::test)]
+async fn test_token_provider_called_per_request() {
+    let mock_server = MockServer::start().await;
+    mock_authenticated_statement(&mock_server, "Bearer token").await;
+
+    let calls = Arc::new(AtomicUsize::new(0));
+    let provider_calls = calls.clone();
+    let client = PrestinoClient::trino(mock_server.uri())
+        .user("me")
+        .authentication(Authentication::token_provider(move || {
+            provider_calls.fetch_add(1, Ordering::SeqCst);
+            Ok("token".to_owned())
+        }))
+        .allow_insecure_authentication(true);
+    client.execute_collect::<(i64,)>("test").await.unwrap();
+    let requests = mock_server.received_requests().await.unwrap().len();
+    assert_eq!(calls.load(Ordering::SeqCst), requests);
+}
+
+#[test(tokio::test)]
+async fn test_insecure_authentication_refused() {
+    let mock_server = MockServer::start().await;
+
+    let client = PrestinoClient::trino(mock_server.uri())
+        .user("me")
+        .authentication(Authentication::basic("user", "secret"));
+    let result = client.execute_collect::<(i64,)>("test").await;
+    assert!(
+        matches!(result, Err(PrestinoError::InsecureAuthenticationError(_))),
+        "{result:?}"
+    );
+    assert!(mock_server.received_requests().await.unwrap().is_empty());
+}
+
+/// Mock a server with external authentication, whose token server makes the client
+/// poll once before returning `token`.
+async fn mock_external_authentication(mock_server: &MockServer, token: &str) {
+    let base_uri = mock_server.uri();
+    Mock::given(method("POST"))
+        .and(path("/v1/statement"))
+        .and(header("authorization", format!("Bearer {token}").as_str()))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string(finished_response(&base_uri, Some(json!([[1]])))),
+        )
+        .mount(mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/statement"))
+        .respond_with(ResponseTemplate::new(401).insert_header(
+            "WWW-Authenticate",
+            format!(
+                r#"Bearer x_redirect_server="{base_uri}/oauth2/token/initiate/1", x_token_server="{base_uri}/oauth2/token/1""#
+            )
+            .as_str(),
+        ))
+        .mount(mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/oauth2/token/1"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({"nextUri": format!("{base_uri}/oauth2/token/2")})),
+        )
+        .expect(1)
+        .mount(mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/oauth2/token/2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "token": token })))
+        .expect(1)
+        .mount(mock_server)
+        .await;
+}
+
+#[test(tokio::test)]
+async fn test_external_authentication() {
+    let mock_server = MockServer::start().await;
+    mock_external_authentication(&mock_server, "external-token").await;
+
+    let redirects = Arc::new(Mutex::new(Vec::new()));
+    let redirected = redirects.clone();
+    let external = ExternalAuthentication::new(move |uri| {
+        redirected.lock().unwrap().push(uri.to_owned());
+        Ok(())
+    });
+    let client = PrestinoClient::trino(mock_server.uri())
+        .user("me")
+        .authentication(Authentication::External(external.clone()))
+        .allow_insecure_authentication(true);
+
+    let rows: Vec<(i64,)> = client.execute_collect("test").await.unwrap();
+    assert_eq!(rows, vec![(1,)]);
+    assert_eq!(
+        *redirects.lock().unwrap(),
+        vec![format!("{}/oauth2/token/initiate/1", mock_server.uri())]
+    );
+    assert_eq!(external.token().as_deref(), Some("external-token"));
+
+    // The token is reused, without authenticating again.
+    let rows: Vec<(i64,)> = client.execute_collect("test").await.unwrap();
+    assert_eq!(rows, vec![(1,)]);
+    assert_eq!(redirects.lock().unwrap().len(), 1);
+    mock_server.verify().await;
+}
+
+#[test(tokio::test)]
+async fn test_external_authentication_error() {
+    let mock_server = MockServer::start().await;
+    let base_uri = mock_server.uri();
+    Mock::given(method("POST"))
+        .and(path("/v1/statement"))
+        .respond_with(ResponseTemplate::new(401).insert_header(
+            "WWW-Authenticate",
+            format!(r#"Bearer x_token_server="{base_uri}/oauth2/token/1""#).as_str(),
+        ))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/oauth2/token/1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"error": "denied"})))
+        .mount(&mock_server)
+        .await;
+
+    let client = PrestinoClient::trino(mock_server.uri())
+        .user("me")
+        .authentication(Authentication::external(|_| Ok(())))
+        .allow_insecure_authentication(true);
+    let result = client.execute_collect::<(i64,)>("test").await;
+    assert!(
+        matches!(&result, Err(PrestinoError::ExternalAuthenticationError(msg)) if msg == "denied"),
+        "{result:?}"
+    );
+}
+
+#[test(tokio::test)]
+async fn test_external_authentication_timeout() {
+    let mock_server = MockServer::start().await;
+    let base_uri = mock_server.uri();
+    Mock::given(method("POST"))
+        .and(path("/v1/statement"))
+        .respond_with(ResponseTemplate::new(401).insert_header(
+            "WWW-Authenticate",
+            format!(r#"Bearer x_token_server="{base_uri}/oauth2/token/1""#).as_str(),
+        ))
+        .mount(&mock_server)
+        .await;
+    // The token server never has the token, and always asks to be polled again.
+    Mock::given(method("GET"))
+        .and(path("/oauth2/token/1"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({"nextUri": format!("{base_uri}/oauth2/token/1")})),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let external = ExternalAuthentication::new(|_| Ok(())).timeout(Duration::from_secs(1));
+    let client = PrestinoClient::trino(mock_server.uri())
+        .user("me")
+        .authentication(Authentication::External(external))
+        .allow_insecure_authentication(true);
+    let start = Instant::now();
+    let result = client.execute_collect::<(i64,)>("test").await;
+    assert!(start.elapsed() < Duration::from_secs(3));
+    assert!(
+        matches!(&result, Err(PrestinoError::ExternalAuthenticationError(_))),
+        "{result:?}"
+    );
+    let polls = mock_server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|request| request.url.path() == "/oauth2/token/1")
+        .count();
+    assert!((2..=5).contains(&polls), "{polls} polls");
+}
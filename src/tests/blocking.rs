@@ -0,0 +1,61 @@
+use super::response_chain::ResponseChain;
+use crate::blocking::PrestinoClient;
+use serde_json::json;
+use test_log::test;
+use tokio::runtime::Runtime;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Start a mock server for a statement with pages of data, on a runtime of its own
+/// since the blocking client can't be used on one.  Returns the runtime, which must
+/// be kept for the server to keep running, the server and the statement's next uris.
+fn mock_statement(data: &[serde_json::Value]) -> (Runtime, MockServer, Vec<String>) {
+    let runtime = Runtime::new().unwrap();
+    let mock_server = runtime.block_on(MockServer::start());
+    let response_strs = ResponseChain::make_response_set(&[("a", "bigint")], data);
+    let response_ref: Vec<&str> = response_strs.iter().map(AsRef::as_ref).collect();
+    let chain = ResponseChain::new(&response_ref, mock_server.uri());
+    runtime.block_on(chain.mock_flow(&mock_server));
+    (runtime, mock_server, chain.next_uris)
+}
+
+#[test]
+fn test_execute_collect() {
+    let (_runtime, mock_server, _) = mock_statement(&[json!([[1], [2]]), json!([]), json!([[3]])]);
+    let client = PrestinoClient::trino(mock_server.uri()).user("me");
+    let rows: Vec<(i64,)> = client.execute_collect("test").unwrap();
+    assert_eq!(rows, vec![(1,), (2,), (3,)]);
+}
+
+#[test]
+fn test_rows_and_batches() {
+    let data = [json!([[1], [2]]), json!([]), json!([[3]])];
+    let (_runtime, mock_server, _) = mock_statement(&data);
+    let client = PrestinoClient::trino(mock_server.uri()).user("me");
+
+    let executor = client.execute::<(i64,)>("test").unwrap();
+    let rows: Vec<(i64,)> = executor.rows().collect::<Result<_, _>>().unwrap();
+    assert_eq!(rows, vec![(1,), (2,), (3,)]);
+
+    let executor = client.execute::<(i64,)>("test").unwrap();
+    let batches: Vec<Vec<(i64,)>> = executor.batches().collect::<Result<_, _>>().unwrap();
+    assert_eq!(batches, vec![vec![(1,), (2,)], vec![(3,)]]);
+}
+
+#[test]
+fn test_drop_cancels() {
+    let (runtime, mock_server, next_uris) = mock_statement(&[json!([[1]]), json!([[2]])]);
+    runtime.block_on(
+        Mock::given(method("DELETE"))
+            .and(path(&next_uris[2]))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&mock_server),
+    );
+    let client = PrestinoClient::trino(mock_server.uri()).user("me");
+
+    let mut rows = client.execute::<(i64,)>("test").unwrap().rows();
+    assert_eq!(rows.next().unwrap().unwrap(), (1,));
+    drop(rows);
+    runtime.block_on(mock_server.verify());
+}
@@ -0,0 +1,116 @@
+use super::response_chain::ResponseChain;
+use crate::PrestinoClient;
+use futures::StreamExt;
+use futures_util::pin_mut;
+use serde_json::json;
+use std::time::Duration;
+use test_log::test;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Mock a statement with two pages of data, returning the uri of the page after the
+/// first page of data.
+async fn mock_statement(mock_server: &MockServer) -> String {
+    let response_strs =
+        ResponseChain::make_response_set(&[("a", "bigint")], &[json!([[1], [2]]), json!([[3]])]);
+    let response_ref: Vec<&str> = response_strs.iter().map(AsRef::as_ref).collect();
+    let chain = ResponseChain::new(&response_ref, mock_server.uri());
+    chain.mock_flow(mock_server).await;
+    chain.next_uris[2].clone()
+}
+
+async fn mock_delete(mock_server: &MockServer, uri: &str, times: u64) {
+    Mock::given(method("DELETE"))
+        .and(path(uri))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(times)
+        .mount(mock_server)
+        .await;
+}
+
+/// Wait for the background requests made on drop.
+async fn wait_for_requests(mock_server: &MockServer, count: usize) {
+    for _ in 0..50 {
+        if mock_server.received_requests().await.unwrap().len() >= count {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
+
+#[test(tokio::test)]
+async fn test_dropped_stream_cancels() {
+    let mock_server = MockServer::start().await;
+    let unread_uri = mock_statement(&mock_server).await;
+    mock_delete(&mock_server, &unread_uri, 1).await;
+
+    let client = PrestinoClient::trino(mock_server.uri()).user("me");
+    let executor = client.execute::<(i64,)>("test".to_string()).await.unwrap();
+    {
+        let rows = executor.rows();
+        pin_mut!(rows);
+        assert_eq!(rows.next().await.unwrap().unwrap(), (1,));
+    }
+
+    // POST, 3 GETs and the DELETE.
+    wait_for_requests(&mock_server, 5).await;
+    mock_server.verify().await;
+}
+
+#[test(tokio::test)]
+async fn test_dropped_executor_cancels() {
+    let mock_server = MockServer::start().await;
+    let response_strs = ResponseChain::make_response_set(&[("a", "bigint")], &[json!([[1]])]);
+    let response_ref: Vec<&str> = response_strs.iter().map(AsRef::as_ref).collect();
+    let chain = ResponseChain::new(&response_ref, mock_server.uri());
+    chain.mock_flow(&mock_server).await;
+    mock_delete(&mock_server, &chain.next_uris[0], 1).await;
+
+    let client = PrestinoClient::trino(mock_server.uri()).user("me");
+    let executor = client.execute::<(i64,)>("test".to_string()).await.unwrap();
+    drop(executor);
+
+    wait_for_requests(&mock_server, 2).await;
+    mock_server.verify().await;
+}
+
+#[test(tokio::test)]
+async fn test_cancel_on_drop_opt_out() {
+    let mock_server = MockServer::start().await;
+    let unread_uri = mock_statement(&mock_server).await;
+    mock_delete(&mock_server, &unread_uri, 0).await;
+
+    let client = PrestinoClient::trino(mock_server.uri()).user("me");
+    let executor = client
+        .execute::<(i64,)>("test".to_string())
+        .await
+        .unwrap()
+        .cancel_on_drop(false);
+    assert!(!executor.is_cancelled_on_drop());
+    {
+        let rows = executor.rows();
+        pin_mut!(rows);
+        assert_eq!(rows.next().await.unwrap().unwrap(), (1,));
+    }
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    mock_server.verify().await;
+}
+
+#[test(tokio::test)]
+async fn test_finished_statement_not_cancelled() {
+    let mock_server = MockServer::start().await;
+    mock_statement(&mock_server).await;
+    Mock::given(method("DELETE"))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(0)
+        .mount(&mock_server)
+        .await;
+
+    let client = PrestinoClient::trino(mock_server.uri()).user("me");
+    let rows: Vec<(i64,)> = client.execute_collect("test".to_string()).await.unwrap();
+    assert_eq!(rows, vec![(1,), (2,), (3,)]);
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    mock_server.verify().await;
+}
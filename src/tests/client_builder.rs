@@ -0,0 +1,115 @@
+use super::finished_response;
+use crate::{PrestinoClient, PrestinoError};
+use reqwest::{Client, Proxy};
+use serde_json::{json, Value};
+use std::time::Duration;
+use test_log::test;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Mock a statement that finishes immediately with a single row.
+async fn mock_statement(mock_server: &MockServer, template: ResponseTemplate) {
+    let response = finished_response(&mock_server.uri(), Some(json!([[1]])));
+    Mock::given(method("POST"))
+        .and(path("/v1/statement"))
+        .respond_with(template.set_body_string(response))
+        .expect(1)
+        .mount(mock_server)
+        .await;
+}
+
+#[test(tokio::test)]
+async fn test_user_agent() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(header("user-agent", "reporting/1.2"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string(finished_response(&mock_server.uri(), Some(json!([[1]])))),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = PrestinoClient::builder(mock_server.uri())
+        .user("me")
+        .user_agent("reporting/1.2")
+        .build()
+        .unwrap();
+    let rows: Vec<Value> = client.execute_collect("SELECT 1").await.unwrap();
+    assert_eq!(rows, vec![json!([1])]);
+}
+
+#[test(tokio::test)]
+async fn test_proxy() {
+    let proxy = MockServer::start().await;
+    mock_statement(&proxy, ResponseTemplate::new(200)).await;
+
+    // The server's host doesn't resolve, so only the proxy can answer.
+    let client = PrestinoClient::builder("http://trino.invalid:8080")
+        .user("me")
+        .proxy(Proxy::http(proxy.uri()).unwrap())
+        .build()
+        .unwrap();
+    let rows: Vec<Value> = client.execute_collect("SELECT 1").await.unwrap();
+    assert_eq!(rows, vec![json!([1])]);
+}
+
+#[test(tokio::test)]
+async fn test_request_timeout() {
+    let mock_server = MockServer::start().await;
+    mock_statement(
+        &mock_server,
+        ResponseTemplate::new(200).set_delay(Duration::from_millis(500)),
+    )
+    .await;
+
+    let client = PrestinoClient::builder(mock_server.uri())
+        .user("me")
+        .connect_timeout(Duration::from_secs(5))
+        .request_timeout(Duration::from_millis(50))
+        .build()
+        .unwrap();
+    let err = client
+        .execute_collect::<Value>("SELECT 1")
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, PrestinoError::HttpError(err) if err.is_timeout()),
+        "{err:?}"
+    );
+}
+
+#[test(tokio::test)]
+async fn test_http_client() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(header("user-agent", "shared"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string(finished_response(&mock_server.uri(), Some(json!([[1]])))),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let http_client = Client::builder().user_agent("shared").build().unwrap();
+    let client = PrestinoClient::builder(format!("{}/", mock_server.uri()))
+        .user("me")
+        .http_client(http_client)
+        .build()
+        .unwrap();
+    let rows: Vec<Value> = client.execute_collect("SELECT 1").await.unwrap();
+    assert_eq!(rows, vec![json!([1])]);
+}
+
+#[test(tokio::test)]
+async fn test_invalid_base_url() {
+    let err = PrestinoClient::builder("localhost:8080")
+        .build()
+        .unwrap_err();
+    assert!(
+        matches!(&err, PrestinoError::BaseUrlError(url, _) if url == "localhost:8080"),
+        "{err:?}"
+    );
+}
@@ -0,0 +1,100 @@
+use super::response_chain::ResponseChain;
+use crate::{PrestinoClient, PrestinoError};
+use serde::Deserialize;
+use serde_json::json;
+use test_log::test;
+use wiremock::MockServer;
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Row {
+    id: i64,
+    name: String,
+    score: Option<f64>,
+}
+
+async fn get_rows_by_name(
+    columns: &[(&str, &str)],
+    data: serde_json::Value,
+) -> Result<Vec<Row>, PrestinoError> {
+    let mock_server = MockServer::start().await;
+    let response_strs = ResponseChain::make_response_set(columns, &[data]);
+    let response_ref: Vec<&str> = response_strs.iter().map(AsRef::as_ref).collect();
+    ResponseChain::new(&response_ref, mock_server.uri())
+        .mock_flow(&mock_server)
+        .await;
+
+    let client = PrestinoClient::trino(mock_server.uri()).user("me");
+    client
+        .execute::<Row>("test")
+        .await?
+        .deserialize_by_name()
+        .collect()
+        .await
+}
+
+#[test(tokio::test)]
+async fn test_reordered_columns() {
+    let rows = get_rows_by_name(
+        &[("name", "varchar"), ("score", "double"), ("id", "bigint")],
+        json!([["a", 1.5, 1], ["b", null, 2]]),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        rows,
+        vec![
+            Row {
+                id: 1,
+                name: "a".to_owned(),
+                score: Some(1.5)
+            },
+            Row {
+                id: 2,
+                name: "b".to_owned(),
+                score: None
+            },
+        ]
+    );
+}
+
+#[test(tokio::test)]
+async fn test_missing_optional_column() {
+    let rows = get_rows_by_name(&[("id", "bigint"), ("name", "varchar")], json!([[1, "a"]]))
+        .await
+        .unwrap();
+    assert_eq!(
+        rows,
+        vec![Row {
+            id: 1,
+            name: "a".to_owned(),
+            score: None
+        }]
+    );
+}
+
+#[test(tokio::test)]
+async fn test_missing_column() {
+    let result = get_rows_by_name(&[("id", "bigint")], json!([[1]])).await;
+    match result {
+        Err(PrestinoError::RowDecodeError(err)) => match &err.source {
+            PrestinoError::ColumnMismatchError(msg) => assert!(msg.contains("name"), "{msg}"),
+            source => panic!("Unexpected source: {source:?}"),
+        },
+        _ => panic!("Unexpected result: {result:?}"),
+    }
+}
+
+#[test(tokio::test)]
+async fn test_extra_column() {
+    let result = get_rows_by_name(
+        &[("id", "bigint"), ("name", "varchar"), ("extra", "boolean")],
+        json!([[1, "a", true]]),
+    )
+    .await;
+    match result {
+        Err(PrestinoError::ColumnMismatchError(msg)) => {
+            assert!(msg.contains("extra"), "{msg}")
+        }
+        _ => panic!("Unexpected result: {result:?}"),
+    }
+}
@@ -1,11 +1,34 @@
+mod adapters;
+mod authentication;
+#[cfg(feature = "blocking")]
+mod blocking;
+mod cancel_on_drop;
+mod client_builder;
+mod deserialize_by_name;
+#[path = "../../tests/common/fixtures.rs"]
+mod fixtures;
+mod poll_backoff;
+mod prepared_statement;
 mod response_chain;
 mod response_set_1;
+mod retry;
+mod session;
+#[cfg(feature = "chrono")]
+mod temporal;
+mod timeout;
+#[cfg(feature = "native-tls")]
+mod tls;
+mod transaction;
 
-use crate::{PrestinoClient, PrestinoError};
+use crate::{results, PrestinoClient, PrestinoError};
+use fixtures::finished_response;
+use futures::TryStreamExt;
+use futures_util::pin_mut;
 use log::debug;
 use response_chain::ResponseChain;
 use serde::de::DeserializeOwned;
 use serde_json::{json, Value};
+use std::collections::HashMap;
 use test_log::test;
 use wiremock::MockServer;
 
@@ -58,3 +81,107 @@ async fn test_basic_types() {
         ],
     );
 }
+
+#[test(tokio::test)]
+async fn test_values() {
+    let mock_server = MockServer::start().await;
+    let response_strs: Vec<String> = ResponseChain::make_response_set(
+        &[("a_int", "integer"), ("a_arr", "array(varchar)")],
+        &[json!([[1, ["a"]]]), json!([]), json!([[null, []]])],
+    );
+    let response_ref: Vec<&str> = response_strs.iter().map(AsRef::as_ref).collect();
+    ResponseChain::new(&response_ref, mock_server.uri())
+        .mock_flow(&mock_server)
+        .await;
+
+    let client = PrestinoClient::trino(mock_server.uri()).user("me");
+    let stream = client.execute("test").await.unwrap().values();
+    pin_mut!(stream);
+    let rows: Vec<Vec<results::Value>> = stream.try_collect().await.unwrap();
+    assert_eq!(
+        rows,
+        vec![
+            vec![
+                results::Value::Integer(1),
+                results::Value::Array(vec![results::Value::Varchar("a".to_owned())])
+            ],
+            vec![results::Value::Null, results::Value::Array(vec![])],
+        ]
+    );
+}
+
+type FloatRow = (f64, Option<f32>, Vec<f64>, HashMap<String, f32>);
+
+#[test(tokio::test)]
+async fn test_special_floats() {
+    let response_strs: Vec<String> = ResponseChain::make_response_set(
+        &[
+            ("a_double", "double"),
+            ("a_real", "real"),
+            ("an_arr", "array(double)"),
+            ("a_map", "map(varchar, real)"),
+        ],
+        &[json!([
+            ["NaN", "Infinity", ["-Infinity", 1.5], {"a": "NaN"}],
+            ["-Infinity", null, [], {}]
+        ])],
+    );
+    let response_ref: Vec<&str> = response_strs.iter().map(AsRef::as_ref).collect();
+    let rows: Vec<FloatRow> = get_rows(&response_ref).await.unwrap();
+    assert!(rows[0].0.is_nan());
+    assert_eq!(rows[0].1, Some(f32::INFINITY));
+    assert_eq!(rows[0].2, vec![f64::NEG_INFINITY, 1.5]);
+    assert!(rows[0].3["a"].is_nan());
+    assert_eq!(rows[1].0, f64::NEG_INFINITY);
+    assert_eq!(rows[1].1, None);
+}
+
+#[test(tokio::test)]
+async fn test_row_decode_error_context() {
+    let response_strs: Vec<String> = ResponseChain::make_response_set(
+        &[("a", "bigint"), ("b", "varchar")],
+        &[json!([[1, "x"], [2, "y"]]), json!([[3, 4]])],
+    );
+    let response_ref: Vec<&str> = response_strs.iter().map(AsRef::as_ref).collect();
+    let query_id: String = serde_json::from_str::<Value>(response_strs[0].as_str()).unwrap()["id"]
+        .as_str()
+        .unwrap()
+        .to_owned();
+    let result: Result<Vec<(i64, String)>, PrestinoError> = get_rows(&response_ref).await;
+    let Err(PrestinoError::RowDecodeError(err)) = result else {
+        panic!("Expected a RowDecodeError, got {result:?}");
+    };
+    assert_eq!(err.query_id, query_id);
+    assert_eq!(err.page.as_deref(), Some("0"));
+    assert_eq!(err.row_index, 2);
+    let column = err.column.as_ref().unwrap();
+    assert_eq!((column.name(), column.type_name()), ("b", "varchar"));
+    assert_eq!(err.row, json!([3, 4]));
+    assert!(err.to_string().contains("row 2"), "{err}");
+}
+
+#[test(tokio::test)]
+async fn test_lenient_rows() {
+    let response_strs: Vec<String> = ResponseChain::make_response_set(
+        &[("a", "bigint"), ("b", "varchar")],
+        &[json!([[1, "x"], [2, 3]]), json!([]), json!([[4, "y"]])],
+    );
+    let response_ref: Vec<&str> = response_strs.iter().map(AsRef::as_ref).collect();
+    let mock_server = MockServer::start().await;
+    ResponseChain::new(&response_ref, mock_server.uri())
+        .mock_flow(&mock_server)
+        .await;
+
+    let presto_client = PrestinoClient::trino(mock_server.uri()).user("me");
+    let executor = presto_client
+        .execute::<(i64, String)>("test".to_string())
+        .await
+        .unwrap();
+    let rows: Vec<_> = executor.lenient_rows().try_collect().await.unwrap();
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[0].as_ref().unwrap(), &(1, "x".to_owned()));
+    let err = rows[1].as_ref().unwrap_err();
+    assert_eq!(err.row_index, 1);
+    assert_eq!(err.row, json!([2, 3]));
+    assert_eq!(rows[2].as_ref().unwrap(), &(4, "y".to_owned()));
+}
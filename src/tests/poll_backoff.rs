@@ -0,0 +1,87 @@
+use super::response_chain::ResponseChain;
+use crate::{PollBackoff, PrestinoClient};
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use test_log::test;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, Request, Respond, ResponseTemplate};
+
+/// Responds with `body`, recording when each request was received.
+struct Recorder {
+    body: String,
+    times: Arc<Mutex<Vec<Instant>>>,
+}
+
+impl Respond for Recorder {
+    fn respond(&self, _request: &Request) -> ResponseTemplate {
+        self.times.lock().unwrap().push(Instant::now());
+        ResponseTemplate::new(200).set_body_string(&self.body)
+    }
+}
+
+/// Run a statement returning the pages of `data`, returning the gaps between the GETs
+/// of its results, in milliseconds.
+async fn poll_gaps(poll_backoff: PollBackoff, data: &[serde_json::Value]) -> Vec<u64> {
+    let mock_server = MockServer::start().await;
+    let response_strs = ResponseChain::make_response_set(&[("a", "bigint")], data);
+    let response_ref: Vec<&str> = response_strs.iter().map(AsRef::as_ref).collect();
+    let chain = ResponseChain::new(&response_ref, mock_server.uri());
+    Mock::given(method("POST"))
+        .and(path("/v1/statement"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(&chain.first_response))
+        .mount(&mock_server)
+        .await;
+    let times = Arc::new(Mutex::new(Vec::new()));
+    for (next_uri, body) in chain.next_uris.iter().zip(&chain.next_responses) {
+        Mock::given(method("GET"))
+            .and(path(next_uri))
+            .respond_with(Recorder {
+                body: body.clone(),
+                times: times.clone(),
+            })
+            .mount(&mock_server)
+            .await;
+    }
+
+    let client = PrestinoClient::trino(mock_server.uri())
+        .user("me")
+        .poll_backoff(poll_backoff);
+    client.execute_collect::<(i64,)>("test").await.unwrap();
+
+    let times = times.lock().unwrap();
+    times
+        .windows(2)
+        .map(|pair| (pair[1] - pair[0]).as_millis() as u64)
+        .collect()
+}
+
+/// Check the gaps are at least what's expected, allowing for some slowness.
+fn assert_gaps(gaps: &[u64], expected: &[u64]) {
+    assert_eq!(gaps.len(), expected.len(), "{gaps:?}");
+    for (gap, expected_gap) in gaps.iter().zip(expected) {
+        assert!(
+            *gap >= *expected_gap && *gap < expected_gap + 80,
+            "gaps {gaps:?}, expected {expected:?}"
+        );
+    }
+}
+
+#[test(tokio::test)]
+async fn test_exponential_backoff_resets_on_data() {
+    let backoff = PollBackoff::exponential(Duration::from_millis(50), Duration::from_millis(200));
+    // The GETs return: no data (running), four pages, then finished with and without
+    // a next uri.
+    let gaps = poll_gaps(backoff, &[json!([]), json!([]), json!([]), json!([[1]])]).await;
+    assert_gaps(&gaps, &[50, 100, 200, 200, 0, 50]);
+}
+
+#[test(tokio::test)]
+async fn test_fixed_backoff() {
+    let gaps = poll_gaps(
+        PollBackoff::fixed(Duration::from_millis(60)),
+        &[json!([[1]]), json!([])],
+    )
+    .await;
+    assert_gaps(&gaps, &[60, 0, 60, 60]);
+}
@@ -0,0 +1,125 @@
+use super::finished_response;
+use crate::{PrestinoClient, PrestinoError};
+use serde_json::json;
+use test_log::test;
+use wiremock::matchers::{body_string, header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const STATEMENT: &str = "SELECT id FROM t WHERE id = ? AND name = ?";
+const ENCODED: &str = "my_query=SELECT+id+FROM+t+WHERE+id+%3D+%3F+AND+name+%3D+%3F";
+
+#[test(tokio::test)]
+async fn test_prepare_execute_deallocate() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/statement"))
+        .and(body_string(format!("PREPARE my_query FROM {STATEMENT}")))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("X-Trino-Added-Prepare", ENCODED)
+                .set_body_string(finished_response(&mock_server.uri(), None)),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/statement"))
+        .and(body_string("EXECUTE my_query USING 1, 'O''Brien'"))
+        .and(header("x-trino-prepared-statement", ENCODED))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string(finished_response(&mock_server.uri(), Some(json!([[1]])))),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/statement"))
+        .and(body_string("EXECUTE my_query USING 2, NULL"))
+        .and(header("x-trino-prepared-statement", ENCODED))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string(finished_response(&mock_server.uri(), Some(json!([])))),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/statement"))
+        .and(body_string("DEALLOCATE PREPARE my_query"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("X-Trino-Deallocated-Prepare", "my_query")
+                .set_body_string(finished_response(&mock_server.uri(), None)),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let session = PrestinoClient::trino(mock_server.uri())
+        .user("me")
+        .session();
+    let prepared = session.prepare("my_query", STATEMENT).await.unwrap();
+    assert_eq!(prepared.statement().as_deref(), Some(STATEMENT));
+
+    let rows: Vec<(i64,)> = prepared.execute_collect(&[&1, &"O'Brien"]).await.unwrap();
+    assert_eq!(rows, vec![(1,)]);
+    let rows: Vec<(i64,)> = prepared
+        .execute_collect(&[&2, &None::<String>])
+        .await
+        .unwrap();
+    assert_eq!(rows, vec![]);
+
+    prepared.deallocate().await.unwrap();
+    assert_eq!(session.headers().get_prepared_statement("my_query"), None);
+}
+
+#[test(tokio::test)]
+async fn test_drop_removes_prepared_statement() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/statement"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("X-Trino-Added-Prepare", ENCODED)
+                .set_body_string(finished_response(&mock_server.uri(), None)),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let session = PrestinoClient::trino(mock_server.uri())
+        .user("me")
+        .session();
+    {
+        let _prepared = session.prepare("my_query", STATEMENT).await.unwrap();
+        assert_eq!(
+            session.headers().get_prepared_statement("my_query"),
+            Some(STATEMENT)
+        );
+    }
+    assert_eq!(session.headers().get_prepared_statement("my_query"), None);
+}
+
+#[test(tokio::test)]
+async fn test_invalid_name() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&mock_server)
+        .await;
+
+    let session = PrestinoClient::trino(mock_server.uri())
+        .user("me")
+        .session();
+    for name in ["x FROM SELECT 1; --", "1st", "my-query", ""] {
+        let err = session.prepare(name, STATEMENT).await.unwrap_err();
+        assert!(
+            matches!(&err, PrestinoError::StatementNameError(n) if n == name),
+            "{err:?}"
+        );
+    }
+}
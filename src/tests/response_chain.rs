@@ -5,7 +5,6 @@ use wiremock::matchers::{method, path};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
 pub struct ResponseChain {
-    pub base_uri: String,
     pub first_response: String,
     pub next_responses: Vec<String>,
     pub next_uris: Vec<String>,
@@ -29,7 +28,6 @@ impl ResponseChain {
             .collect();
         let first_response = responses.remove(0).to_string();
         ResponseChain {
-            base_uri,
             first_response,
             next_responses: responses,
             next_uris,
@@ -48,7 +46,7 @@ impl ResponseChain {
         Mock::given(method("POST"))
             .and(path("/v1/statement"))
             .respond_with(first_response)
-            .mount(&mock_server)
+            .mount(mock_server)
             .await;
 
         for (next_uri, next_json) in self.next_uris.iter().zip(self.next_responses.iter()) {
@@ -57,7 +55,7 @@ impl ResponseChain {
             Mock::given(method("GET"))
                 .and(path(next_uri))
                 .respond_with(next_response)
-                .mount(&mock_server)
+                .mount(mock_server)
                 .await;
         }
     }
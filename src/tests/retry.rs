@@ -0,0 +1,138 @@
+use super::response_chain::ResponseChain;
+use crate::{PrestinoClient, PrestinoError, RetryPolicy};
+use serde_json::json;
+use std::time::Duration;
+use test_log::test;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Mock a statement with one page of data, whose first GET fails `failures` times
+/// with `status`, returning the uri of that GET.
+async fn mock_failing_statement(mock_server: &MockServer, status: u16, failures: u64) -> String {
+    let response_strs = ResponseChain::make_response_set(&[("a", "bigint")], &[json!([[1]])]);
+    let response_ref: Vec<&str> = response_strs.iter().map(AsRef::as_ref).collect();
+    let chain = ResponseChain::new(&response_ref, mock_server.uri());
+    let next_uri = chain.next_uris[0].clone();
+    // Mounted first, so it takes precedence until it's used up.
+    Mock::given(method("GET"))
+        .and(path(&next_uri))
+        .respond_with(ResponseTemplate::new(status))
+        .up_to_n_times(failures)
+        .expect(failures)
+        .mount(mock_server)
+        .await;
+    chain.mock_flow(mock_server).await;
+    next_uri
+}
+
+fn fast_retries() -> RetryPolicy {
+    RetryPolicy::default().backoff(Duration::from_millis(1), Duration::from_millis(10))
+}
+
+#[test(tokio::test)]
+async fn test_transient_failures_retried() {
+    let mock_server = MockServer::start().await;
+    mock_failing_statement(&mock_server, 502, 2).await;
+
+    let client = PrestinoClient::trino(mock_server.uri())
+        .user("me")
+        .retry_policy(fast_retries());
+    let rows: Vec<(i64,)> = client.execute_collect("test").await.unwrap();
+    assert_eq!(rows, vec![(1,)]);
+    mock_server.verify().await;
+}
+
+#[test(tokio::test)]
+async fn test_retries_exhausted() {
+    let mock_server = MockServer::start().await;
+    mock_failing_statement(&mock_server, 504, 3).await;
+
+    let client = PrestinoClient::trino(mock_server.uri())
+        .user("me")
+        .retry_policy(fast_retries().max_attempts(3));
+    let result = client.execute_collect::<(i64,)>("test").await;
+    assert!(
+        matches!(result, Err(PrestinoError::StatusCodeError(504, _))),
+        "{result:?}"
+    );
+    mock_server.verify().await;
+}
+
+#[test(tokio::test)]
+async fn test_failed_statement_cancelled_on_drop() {
+    let mock_server = MockServer::start().await;
+    let next_uri = mock_failing_statement(&mock_server, 504, 2).await;
+    Mock::given(method("DELETE"))
+        .and(path(next_uri))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = PrestinoClient::trino(mock_server.uri())
+        .user("me")
+        .retry_policy(fast_retries().max_attempts(2));
+    let mut executor = client.execute::<(i64,)>("test").await.unwrap();
+    let result = executor.next_response().await.unwrap();
+    assert!(
+        matches!(result, Err(PrestinoError::StatusCodeError(504, _))),
+        "{result:?}"
+    );
+    assert!(executor.next_response().await.is_none());
+    drop(executor);
+
+    // POST, 2 failed GETs and the DELETE.
+    for _ in 0..50 {
+        if mock_server.received_requests().await.unwrap().len() >= 4 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    mock_server.verify().await;
+}
+
+#[test(tokio::test)]
+async fn test_permanent_failure_not_retried() {
+    let mock_server = MockServer::start().await;
+    mock_failing_statement(&mock_server, 404, 1).await;
+
+    let client = PrestinoClient::trino(mock_server.uri())
+        .user("me")
+        .retry_policy(fast_retries());
+    let result = client.execute_collect::<(i64,)>("test").await;
+    assert!(
+        matches!(result, Err(PrestinoError::StatusCodeError(404, _))),
+        "{result:?}"
+    );
+    mock_server.verify().await;
+}
+
+#[test(tokio::test)]
+async fn test_cancel_retried() {
+    let mock_server = MockServer::start().await;
+    let response_strs = ResponseChain::make_response_set(&[("a", "bigint")], &[json!([[1]])]);
+    let response_ref: Vec<&str> = response_strs.iter().map(AsRef::as_ref).collect();
+    let chain = ResponseChain::new(&response_ref, mock_server.uri());
+    chain.mock_flow(&mock_server).await;
+    let next_uri = &chain.next_uris[0];
+    Mock::given(method("DELETE"))
+        .and(path(next_uri))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("DELETE"))
+        .and(path(next_uri))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = PrestinoClient::trino(mock_server.uri())
+        .user("me")
+        .retry_policy(fast_retries());
+    let executor = client.execute::<(i64,)>("test").await.unwrap();
+    executor.cancel().await.unwrap();
+    mock_server.verify().await;
+}
@@ -0,0 +1,71 @@
+use super::finished_response;
+use crate::PrestinoClient;
+use serde_json::{json, Value};
+use test_log::test;
+use wiremock::matchers::{body_string, header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[test(tokio::test)]
+async fn test_session_keeps_server_state() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/statement"))
+        .and(body_string("USE tpch.tiny"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("X-Trino-Set-Catalog", "tpch")
+                .insert_header("X-Trino-Set-Schema", "tiny")
+                .set_body_string(finished_response(&mock_server.uri(), None)),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/statement"))
+        .and(body_string("SET SESSION query_max_run_time = '1h'"))
+        .and(header("x-trino-catalog", "tpch"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("X-Trino-Set-Session", "query_max_run_time=1h")
+                .set_body_string(finished_response(&mock_server.uri(), None)),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/statement"))
+        .and(body_string("SELECT name FROM nation"))
+        .and(header("x-trino-catalog", "tpch"))
+        .and(header("x-trino-schema", "tiny"))
+        .and(header("x-trino-session", "query_max_run_time=1h"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string(finished_response(
+                &mock_server.uri(),
+                Some(json!([["ALGERIA"]])),
+            )),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = PrestinoClient::trino(mock_server.uri()).user("me");
+    let session = client.session();
+    session
+        .execute_collect::<Value>("USE tpch.tiny")
+        .await
+        .unwrap();
+    session
+        .execute_collect::<Value>("SET SESSION query_max_run_time = '1h'")
+        .await
+        .unwrap();
+    let rows: Vec<(String,)> = session
+        .execute_collect("SELECT name FROM nation")
+        .await
+        .unwrap();
+    assert_eq!(rows, vec![("ALGERIA".to_owned(),)]);
+
+    // The client itself is unchanged.
+    let header_map = client.headers().build().unwrap();
+    assert!(!header_map.contains_key("x-trino-catalog"));
+}
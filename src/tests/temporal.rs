@@ -0,0 +1,73 @@
+use super::finished_response;
+use crate::results::temporal::chrono::{timestamp_in_session_zone, timestamp_with_time_zone};
+use crate::PrestinoClient;
+use chrono::{DateTime, FixedOffset};
+use serde::Deserialize;
+use serde_json::json;
+use test_log::test;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[derive(Debug, Deserialize)]
+struct Row {
+    #[serde(with = "timestamp_in_session_zone")]
+    local: DateTime<FixedOffset>,
+    #[serde(with = "timestamp_with_time_zone")]
+    zoned: DateTime<FixedOffset>,
+}
+
+#[test(tokio::test)]
+async fn test_session_time_zone() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/statement"))
+        .and(header("x-trino-time-zone", "america/los_angeles"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string(finished_response(
+                &mock_server.uri(),
+                Some(json!([[
+                    "2022-07-01 12:00:00.000",
+                    "2022-07-01 12:00:00.000 America/Los_Angeles"
+                ]])),
+            )),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let mut client = PrestinoClient::trino(mock_server.uri()).user("me");
+    client.headers_mut().set_timezone("America/Los_Angeles");
+    let rows: Vec<Row> = client
+        .execute_collect("SELECT ts, CAST(ts AS TIMESTAMP WITH TIME ZONE) FROM t")
+        .await
+        .unwrap();
+    assert_eq!(rows[0].local.to_rfc3339(), "2022-07-01T12:00:00-07:00");
+    assert_eq!(rows[0].local, rows[0].zoned);
+}
+
+#[test(tokio::test)]
+async fn test_no_session_time_zone() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/statement"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string(finished_response(
+                &mock_server.uri(),
+                Some(json!([[
+                    "2022-07-01 12:00:00.000",
+                    "2022-07-01 19:00:00.000 UTC"
+                ]])),
+            )),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = PrestinoClient::trino(mock_server.uri()).user("me");
+    let result = client
+        .execute_collect::<Row>("SELECT ts, CAST(ts AS TIMESTAMP WITH TIME ZONE) FROM t")
+        .await;
+    assert!(result.is_err());
+}
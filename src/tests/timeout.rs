@@ -0,0 +1,131 @@
+use crate::{PrestinoClient, PrestinoError};
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+use test_log::test;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const NEXT_PATH: &str = "/v1/statement/queued/20221204_000000_00000_abcde/y1/1";
+
+/// A queued response, whose next uri returns the same response.
+fn queued_response(base_uri: &str) -> String {
+    json!({
+        "id": "20221204_000000_00000_abcde",
+        "infoUri": format!("{base_uri}/ui/query.html"),
+        "nextUri": format!("{base_uri}{NEXT_PATH}"),
+        "stats": {
+            "state": "QUEUED",
+            "queued": true,
+            "scheduled": false,
+            "nodes": 0,
+            "totalSplits": 0,
+            "queuedSplits": 0,
+            "runningSplits": 0,
+            "completedSplits": 0,
+            "cpuTimeMillis": 0,
+            "wallTimeMillis": 0,
+            "queuedTimeMillis": 0,
+            "elapsedTimeMillis": 0,
+            "processedRows": 0,
+            "processedBytes": 0,
+            "physicalInputBytes": 0,
+            "peakMemoryBytes": 0,
+            "spilledBytes": 0
+        }
+    })
+    .to_string()
+}
+
+/// Mock a statement that stays queued, with each poll taking `delay`.
+async fn mock_queued_statement(mock_server: &MockServer, delay: Duration) {
+    let response = queued_response(&mock_server.uri());
+    Mock::given(method("POST"))
+        .and(path("/v1/statement"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(&response))
+        .mount(mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(NEXT_PATH))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string(&response)
+                .set_delay(delay),
+        )
+        .mount(mock_server)
+        .await;
+    Mock::given(method("DELETE"))
+        .and(path(NEXT_PATH))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(1)
+        .mount(mock_server)
+        .await;
+}
+
+#[test(tokio::test)]
+async fn test_client_timeout() {
+    let mock_server = MockServer::start().await;
+    mock_queued_statement(&mock_server, Duration::from_secs(5)).await;
+
+    let client = PrestinoClient::trino(mock_server.uri())
+        .user("me")
+        .timeout(Duration::from_millis(200));
+    let start = Instant::now();
+    let result = client.execute_collect::<Value>("test").await;
+    assert!(start.elapsed() < Duration::from_secs(2));
+    match result {
+        Err(PrestinoError::Timeout(id, stats)) => {
+            assert_eq!(id, "20221204_000000_00000_abcde");
+            assert_eq!(stats.state, "QUEUED");
+        }
+        _ => panic!("Unexpected result: {result:?}"),
+    }
+    mock_server.verify().await;
+}
+
+#[test(tokio::test)]
+async fn test_statement_progress_timeout() {
+    let mock_server = MockServer::start().await;
+    mock_queued_statement(&mock_server, Duration::from_millis(10)).await;
+
+    let client = PrestinoClient::trino(mock_server.uri())
+        .user("me")
+        .progress_timeout(Duration::from_secs(60));
+    let executor = client
+        .execute::<Value>("test")
+        .await
+        .unwrap()
+        .progress_timeout(Duration::from_millis(300));
+    let start = Instant::now();
+    let result = executor.collect().await;
+    assert!(start.elapsed() >= Duration::from_millis(300));
+    assert!(
+        matches!(result, Err(PrestinoError::Timeout(..))),
+        "{result:?}"
+    );
+    mock_server.verify().await;
+}
+
+#[test(tokio::test)]
+async fn test_timeout_before_statement_accepted() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/statement"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string(queued_response(&mock_server.uri()))
+                .set_delay(Duration::from_secs(5)),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let client = PrestinoClient::trino(mock_server.uri())
+        .user("me")
+        .timeout(Duration::from_millis(200));
+    let start = Instant::now();
+    let result = client.execute_collect::<Value>("test").await;
+    assert!(start.elapsed() < Duration::from_secs(2));
+    match result {
+        Err(PrestinoError::Timeout(id, _)) => assert_eq!(id, ""),
+        _ => panic!("Unexpected result: {result:?}"),
+    }
+}
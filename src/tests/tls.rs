@@ -0,0 +1,159 @@
+use super::finished_response;
+use crate::{PrestinoClient, PrestinoClientBuilder, PrestinoError};
+use openssl::ssl::{SslAcceptor, SslFiletype, SslMethod, SslVerifyMode};
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread;
+use test_log::test;
+
+fn tls_file(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("test-resources/tls")
+        .join(name)
+}
+
+/// Start a TLS server on `ip` with the certificate in test-resources/tls, which
+/// answers every statement with a single row.  If `require_client_cert`, it only
+/// accepts clients presenting a certificate signed by the test CA.
+fn start_tls_server(ip: &str, require_client_cert: bool) -> SocketAddr {
+    let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).unwrap();
+    acceptor
+        .set_certificate_chain_file(tls_file("server.pem"))
+        .unwrap();
+    acceptor
+        .set_private_key_file(tls_file("server.key"), SslFiletype::PEM)
+        .unwrap();
+    if require_client_cert {
+        acceptor.set_ca_file(tls_file("ca.pem")).unwrap();
+        acceptor.set_verify(SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT);
+    }
+    let acceptor = Arc::new(acceptor.build());
+    let listener = TcpListener::bind((ip, 0)).unwrap();
+    let addr = listener.local_addr().unwrap();
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let acceptor = acceptor.clone();
+            thread::spawn(move || {
+                // Failed handshakes are what some tests expect.
+                if let Ok(stream) = acceptor.accept(stream) {
+                    let _ = respond(stream, addr);
+                }
+            });
+        }
+    });
+    addr
+}
+
+fn respond(stream: openssl::ssl::SslStream<TcpStream>, addr: SocketAddr) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream);
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+    reader.read_exact(&mut vec![0; content_length])?;
+    let body = finished_response(&format!("https://{addr}"), Some(json!([[1]])));
+    let mut stream = reader.into_inner();
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()
+}
+
+async fn select_one(
+    client: Result<PrestinoClient, PrestinoError>,
+) -> Result<Vec<Value>, PrestinoError> {
+    client?.execute_collect("SELECT 1").await
+}
+
+fn builder(addr: SocketAddr) -> PrestinoClientBuilder {
+    PrestinoClient::builder(format!("https://localhost:{}", addr.port())).user("me")
+}
+
+#[test(tokio::test)]
+async fn test_root_certificate() {
+    let addr = start_tls_server("127.0.0.1", false);
+    let client = builder(addr)
+        .root_certificate_file(tls_file("ca.pem"))
+        .build();
+    assert_eq!(select_one(client).await.unwrap(), vec![json!([1])]);
+}
+
+#[test(tokio::test)]
+async fn test_untrusted_certificate() {
+    let addr = start_tls_server("127.0.0.1", false);
+    let err = select_one(builder(addr).build()).await.unwrap_err();
+    assert!(matches!(err, PrestinoError::HttpError(_)), "{err:?}");
+}
+
+#[test(tokio::test)]
+async fn test_accept_invalid_certs() {
+    let addr = start_tls_server("127.0.0.1", false);
+    let client = builder(addr).danger_accept_invalid_certs(true).build();
+    assert_eq!(select_one(client).await.unwrap(), vec![json!([1])]);
+}
+
+#[test(tokio::test)]
+async fn test_hostname_verification() {
+    // The server certificate is for localhost and 127.0.0.1 only.
+    let addr = start_tls_server("127.0.0.2", false);
+    let builder = PrestinoClient::builder(format!("https://{addr}"))
+        .user("me")
+        .root_certificate_file(tls_file("ca.pem"));
+    let err = select_one(builder.clone().build()).await.unwrap_err();
+    assert!(matches!(err, PrestinoError::HttpError(_)), "{err:?}");
+
+    let client = builder.danger_accept_invalid_hostnames(true).build();
+    assert_eq!(select_one(client).await.unwrap(), vec![json!([1])]);
+}
+
+#[test(tokio::test)]
+async fn test_client_certificate() {
+    let addr = start_tls_server("127.0.0.1", true);
+    let builder = builder(addr).root_certificate_file(tls_file("ca.pem"));
+
+    let err = select_one(builder.clone().build()).await.unwrap_err();
+    assert!(matches!(err, PrestinoError::HttpError(_)), "{err:?}");
+
+    let client = builder
+        .clone()
+        .identity_pem_files(tls_file("client.pem"), tls_file("client.key"))
+        .build();
+    assert_eq!(select_one(client).await.unwrap(), vec![json!([1])]);
+
+    let client = builder
+        .identity_pkcs12_file(tls_file("client.p12"), "prestino")
+        .build();
+    assert_eq!(select_one(client).await.unwrap(), vec![json!([1])]);
+}
+
+#[test(tokio::test)]
+async fn test_invalid_files() {
+    let builder = PrestinoClient::builder("https://localhost");
+    let err = builder
+        .clone()
+        .root_certificate_file(tls_file("missing.pem"))
+        .build()
+        .unwrap_err();
+    assert!(matches!(err, PrestinoError::TlsConfigError(_)), "{err:?}");
+
+    let err = builder
+        .identity_pkcs12_file(tls_file("client.p12"), "wrong password")
+        .build()
+        .unwrap_err();
+    assert!(matches!(err, PrestinoError::TlsConfigError(_)), "{err:?}");
+}
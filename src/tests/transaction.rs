@@ -0,0 +1,157 @@
+use super::finished_response;
+use crate::{IsolationLevel, PrestinoClient, PrestinoError};
+use serde_json::Value;
+use std::time::Duration;
+use test_log::test;
+use wiremock::matchers::{body_string, header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const TRANSACTION_ID: &str = "bd8a2f0c-4f5e-4d0b-9a53-1c1f1e5b9a77";
+
+async fn mock_statement(
+    mock_server: &MockServer,
+    statement: &str,
+    response_header: Option<(&str, &str)>,
+) {
+    let mut response =
+        ResponseTemplate::new(200).set_body_string(finished_response(&mock_server.uri(), None));
+    if let Some((name, value)) = response_header {
+        response = response.insert_header(name, value);
+    }
+    Mock::given(method("POST"))
+        .and(path("/v1/statement"))
+        .and(body_string(statement))
+        .and(header("x-trino-transaction-id", TRANSACTION_ID))
+        .respond_with(response)
+        .expect(1)
+        .mount(mock_server)
+        .await;
+}
+
+async fn mock_start_transaction(mock_server: &MockServer, statement: &str) {
+    Mock::given(method("POST"))
+        .and(path("/v1/statement"))
+        .and(body_string(statement))
+        .and(header("x-trino-transaction-id", "none"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("X-Trino-Started-Transaction-Id", TRANSACTION_ID)
+                .set_body_string(finished_response(&mock_server.uri(), None)),
+        )
+        .expect(1)
+        .mount(mock_server)
+        .await;
+}
+
+#[test(tokio::test)]
+async fn test_commit() {
+    let mock_server = MockServer::start().await;
+    mock_start_transaction(
+        &mock_server,
+        "START TRANSACTION ISOLATION LEVEL SERIALIZABLE, READ WRITE",
+    )
+    .await;
+    mock_statement(&mock_server, "INSERT INTO t VALUES 1", None).await;
+    mock_statement(
+        &mock_server,
+        "COMMIT",
+        Some(("X-Trino-Clear-Transaction-Id", "true")),
+    )
+    .await;
+
+    let session = PrestinoClient::trino(mock_server.uri())
+        .user("me")
+        .session();
+    let transaction = session
+        .begin_transaction(IsolationLevel::Serializable, false)
+        .await
+        .unwrap();
+    assert_eq!(transaction.id(), TRANSACTION_ID);
+    transaction
+        .execute_collect::<Value>("INSERT INTO t VALUES 1")
+        .await
+        .unwrap();
+    transaction.commit().await.unwrap();
+    assert_eq!(session.headers().get_transaction_id(), None);
+}
+
+#[test(tokio::test)]
+async fn test_rollback() {
+    let mock_server = MockServer::start().await;
+    mock_start_transaction(
+        &mock_server,
+        "START TRANSACTION ISOLATION LEVEL READ COMMITTED, READ ONLY",
+    )
+    .await;
+    mock_statement(
+        &mock_server,
+        "ROLLBACK",
+        Some(("X-Trino-Clear-Transaction-Id", "true")),
+    )
+    .await;
+
+    let session = PrestinoClient::trino(mock_server.uri())
+        .user("me")
+        .session();
+    let transaction = session
+        .begin_transaction(IsolationLevel::ReadCommitted, true)
+        .await
+        .unwrap();
+    transaction.rollback().await.unwrap();
+    assert_eq!(session.headers().get_transaction_id(), None);
+}
+
+#[test(tokio::test)]
+async fn test_rollback_on_drop() {
+    let mock_server = MockServer::start().await;
+    mock_start_transaction(
+        &mock_server,
+        "START TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ WRITE",
+    )
+    .await;
+    mock_statement(&mock_server, "ROLLBACK", None).await;
+
+    let session = PrestinoClient::trino(mock_server.uri())
+        .user("me")
+        .session();
+    {
+        let _transaction = session
+            .begin_transaction(IsolationLevel::RepeatableRead, false)
+            .await
+            .unwrap();
+    }
+    assert_eq!(session.headers().get_transaction_id(), None);
+
+    // The rollback is sent in the background.
+    for _ in 0..50 {
+        if mock_server.received_requests().await.unwrap().len() == 2 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    mock_server.verify().await;
+}
+
+#[test(tokio::test)]
+async fn test_nested_transaction() {
+    let mock_server = MockServer::start().await;
+    mock_start_transaction(
+        &mock_server,
+        "START TRANSACTION ISOLATION LEVEL SERIALIZABLE, READ WRITE",
+    )
+    .await;
+    mock_statement(&mock_server, "COMMIT", None).await;
+
+    let session = PrestinoClient::trino(mock_server.uri())
+        .user("me")
+        .session();
+    let transaction = session
+        .begin_transaction(IsolationLevel::Serializable, false)
+        .await
+        .unwrap();
+    let result = session
+        .begin_transaction(IsolationLevel::Serializable, false)
+        .await;
+    assert!(matches!(result, Err(PrestinoError::TransactionError(_))));
+    transaction.commit().await.unwrap();
+}
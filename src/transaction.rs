@@ -0,0 +1,164 @@
+use crate::{Headers, PrestinoError, Session, StatementExecutor};
+use log::{debug, warn};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+/// The isolation level for a transaction.  See
+/// [START TRANSACTION](https://trino.io/docs/current/sql/start-transaction.html).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum IsolationLevel {
+    ReadUncommitted,
+    ReadCommitted,
+    RepeatableRead,
+    Serializable,
+}
+
+impl IsolationLevel {
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            IsolationLevel::ReadUncommitted => "READ UNCOMMITTED",
+            IsolationLevel::ReadCommitted => "READ COMMITTED",
+            IsolationLevel::RepeatableRead => "REPEATABLE READ",
+            IsolationLevel::Serializable => "SERIALIZABLE",
+        }
+    }
+}
+
+/// A transaction in a Session, created by `Session::begin_transaction`.  Statements
+/// run through the Transaction (or its Session) while it is open are part of the transaction.
+///
+/// The transaction must be finished with `commit()` or `rollback()`; if it is dropped
+/// while still open, it is rolled back in the background.
+#[derive(Debug)]
+pub struct Transaction<'a> {
+    session: &'a Session,
+    id: String,
+    finished: bool,
+}
+
+impl<'a> Transaction<'a> {
+    pub(crate) async fn begin(
+        session: &'a Session,
+        isolation: IsolationLevel,
+        read_only: bool,
+    ) -> Result<Transaction<'a>, PrestinoError> {
+        if let Some(id) = session.lock_headers().get_transaction_id() {
+            return Err(PrestinoError::TransactionError(format!(
+                "Session is already in transaction {id}"
+            )));
+        }
+
+        let access_mode = if read_only { "READ ONLY" } else { "READ WRITE" };
+        let statement = format!(
+            "START TRANSACTION ISOLATION LEVEL {}, {}",
+            isolation.as_sql(),
+            access_mode
+        );
+        // The server only allows transactions for clients that declare they support them,
+        // which is done with a transaction id of NONE.
+        let headers = session
+            .client()
+            .headers()
+            .new_with_fork()
+            .transaction_id("NONE");
+        session
+            .execute_collect_with_headers::<Value>(statement, &headers)
+            .await?;
+
+        let Some(id) = session
+            .lock_headers()
+            .get_transaction_id()
+            .map(str::to_owned)
+        else {
+            return Err(PrestinoError::TransactionError(
+                "Server did not start a transaction".to_owned(),
+            ));
+        };
+        debug!("Started transaction {id}");
+        Ok(Transaction {
+            session,
+            id,
+            finished: false,
+        })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn session(&self) -> &Session {
+        self.session
+    }
+
+    /// Begin execution of a statement in this transaction, returning a StatementExecutor
+    /// to continue execution.
+    pub async fn execute<T: DeserializeOwned>(
+        &self,
+        statement: impl Into<String>,
+    ) -> Result<StatementExecutor<T>, PrestinoError> {
+        self.session.execute(statement).await
+    }
+
+    /// A convenience function to retrieve all the rows for a statement in this
+    /// transaction into a single Vec.
+    pub async fn execute_collect<T: DeserializeOwned>(
+        &self,
+        statement: impl Into<String>,
+    ) -> Result<Vec<T>, PrestinoError> {
+        self.session.execute_collect(statement).await
+    }
+
+    /// Commit the transaction.
+    pub async fn commit(mut self) -> Result<(), PrestinoError> {
+        self.finish("COMMIT").await
+    }
+
+    /// Roll back the transaction.
+    pub async fn rollback(mut self) -> Result<(), PrestinoError> {
+        self.finish("ROLLBACK").await
+    }
+
+    async fn finish(&mut self, statement: &str) -> Result<(), PrestinoError> {
+        // Whether or not this succeeds, the server will not continue the transaction.
+        self.finished = true;
+        let result = self.session.execute_collect::<Value>(statement).await;
+        self.clear_session_transaction_id();
+        result.map(|_| ())
+    }
+
+    /// The server clears the transaction id when the transaction finishes, but if
+    /// the request failed we still don't want to send later statements to it.
+    fn clear_session_transaction_id(&self) {
+        let mut headers = self.session.lock_headers();
+        if headers.get_transaction_id() == Some(self.id.as_str()) {
+            headers.clear_transaction_id();
+        }
+    }
+}
+
+impl<'a> Drop for Transaction<'a> {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        debug!("Rolling back dropped transaction {}", self.id);
+        let mut headers: Headers = self.session.headers();
+        headers.set_transaction_id(&self.id);
+        self.clear_session_transaction_id();
+
+        let client = self.session.client().clone();
+        let id = self.id.clone();
+        crate::runtime::spawn(async move {
+            let result = match client
+                .start_statement::<Value>("ROLLBACK", headers, None)
+                .await
+            {
+                Ok(executor) => executor.collect().await.map(|_| ()),
+                Err(err) => Err(err),
+            };
+            if let Err(err) = result {
+                warn!("Failed to roll back transaction {id}: {err}");
+            }
+        });
+    }
+}
@@ -0,0 +1,536 @@
+use crate::results::Column;
+use serde::de::{self, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde_json::{Error, Map, Value};
+use std::cell::Cell;
+
+/// Deserializes a row from the JSON sent by the server.  This is like deserializing
+/// from the `serde_json::Value` directly, except that the server sends the floating
+/// point values NaN, Infinity and -Infinity as the strings `"NaN"`, `"Infinity"` and
+/// `"-Infinity"`, which this accepts for `f32` and `f64` at any depth (e.g. in arrays,
+/// maps and nested rows).
+pub(crate) struct ValueDeserializer<'de>(&'de Value);
+
+fn special_float(value: &Value) -> Option<f64> {
+    match value.as_str()? {
+        "NaN" => Some(f64::NAN),
+        "Infinity" => Some(f64::INFINITY),
+        "-Infinity" => Some(f64::NEG_INFINITY),
+        _ => None,
+    }
+}
+
+macro_rules! delegate_to_value {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                self.0.$method(visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for ValueDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0 {
+            Value::Array(values) => visit_array(values, visitor),
+            Value::Object(map) => visit_object(map, visitor),
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match special_float(self.0) {
+            Some(float) => visitor.visit_f32(float as f32),
+            None => self.0.deserialize_f32(visitor),
+        }
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match special_float(self.0) {
+            Some(float) => visitor.visit_f64(float),
+            None => self.0.deserialize_f64(visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0 {
+            Value::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0 {
+            Value::Array(values) => visit_array(values, visitor),
+            value => value.deserialize_seq(visitor),
+        }
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0 {
+            Value::Object(map) => visit_object(map, visitor),
+            value => value.deserialize_map(visitor),
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match self.0 {
+            Value::Array(values) => visit_array(values, visitor),
+            Value::Object(map) => visit_object(map, visitor),
+            value => value.deserialize_struct(name, fields, visitor),
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.0.deserialize_unit_struct(name, visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.0.deserialize_enum(name, variants, visitor)
+    }
+
+    delegate_to_value! {
+        deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64
+        deserialize_i128 deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64
+        deserialize_u128 deserialize_char deserialize_str deserialize_string deserialize_bytes
+        deserialize_byte_buf deserialize_unit deserialize_identifier deserialize_ignored_any
+    }
+}
+
+fn visit_array<'de, V: Visitor<'de>>(values: &'de [Value], visitor: V) -> Result<V::Value, Error> {
+    let len = values.len();
+    let mut seq = SeqDeserializer(values.iter());
+    let result = visitor.visit_seq(&mut seq)?;
+    if seq.0.len() == 0 {
+        Ok(result)
+    } else {
+        Err(de::Error::invalid_length(len, &"fewer elements in array"))
+    }
+}
+
+fn visit_object<'de, V: Visitor<'de>>(
+    map: &'de Map<String, Value>,
+    visitor: V,
+) -> Result<V::Value, Error> {
+    let len = map.len();
+    let mut map = MapDeserializer {
+        entries: map.iter(),
+        value: None,
+    };
+    let result = visitor.visit_map(&mut map)?;
+    if map.entries.len() == 0 {
+        Ok(result)
+    } else {
+        Err(de::Error::invalid_length(len, &"fewer elements in map"))
+    }
+}
+
+struct SeqDeserializer<'de>(std::slice::Iter<'de, Value>);
+
+impl<'de> SeqAccess<'de> for SeqDeserializer<'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        self.0
+            .next()
+            .map(|value| seed.deserialize(ValueDeserializer(value)))
+            .transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.0.len())
+    }
+}
+
+struct MapDeserializer<'de> {
+    entries: serde_json::map::Iter<'de>,
+    value: Option<&'de Value>,
+}
+
+impl<'de> MapAccess<'de> for MapDeserializer<'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        match self.entries.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(MapKeyDeserializer(key)).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        match self.value.take() {
+            Some(value) => seed.deserialize(ValueDeserializer(value)),
+            None => Err(de::Error::custom("value is missing")),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+/// Deserializes a row from its values, one per column.  The row is a sequence of the
+/// values, or with `columns`, a map from the column names to the values.  The index of
+/// the column being deserialized is kept in `current_column`, so errors can name it.
+#[derive(Clone, Copy)]
+pub(crate) struct RowDeserializer<'de> {
+    values: &'de [Value],
+    columns: Option<&'de [Column]>,
+    current_column: &'de Cell<Option<usize>>,
+}
+
+impl<'de> RowDeserializer<'de> {
+    pub fn positional(values: &'de [Value], current_column: &'de Cell<Option<usize>>) -> Self {
+        Self {
+            values,
+            columns: None,
+            current_column,
+        }
+    }
+
+    /// The values must be in the same order as `columns`.
+    pub fn by_name(
+        values: &'de [Value],
+        columns: &'de [Column],
+        current_column: &'de Cell<Option<usize>>,
+    ) -> Self {
+        Self {
+            values,
+            columns: Some(columns),
+            current_column,
+        }
+    }
+
+    fn visit_row<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.columns {
+            Some(columns) => visitor.visit_map(RowMapAccess {
+                row: self,
+                columns,
+                index: 0,
+            }),
+            None => self.visit_values(visitor),
+        }
+    }
+
+    fn visit_values<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let mut seq = RowSeqAccess {
+            row: self,
+            index: 0,
+        };
+        let result = visitor.visit_seq(&mut seq)?;
+        if seq.index == self.values.len() {
+            Ok(result)
+        } else {
+            Err(de::Error::invalid_length(
+                self.values.len(),
+                &"fewer columns in the row",
+            ))
+        }
+    }
+
+    fn deserialize_column<T: DeserializeSeed<'de>>(
+        &self,
+        index: usize,
+        seed: T,
+    ) -> Result<T::Value, Error> {
+        self.current_column.set(Some(index));
+        let value = seed.deserialize(ValueDeserializer(&self.values[index]))?;
+        self.current_column.set(None);
+        Ok(value)
+    }
+}
+
+impl<'de> Deserializer<'de> for RowDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.visit_row(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.visit_values(visitor)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.visit_values(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.visit_values(visitor)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct map struct enum identifier ignored_any
+    }
+}
+
+struct RowSeqAccess<'de> {
+    row: RowDeserializer<'de>,
+    index: usize,
+}
+
+impl<'de> SeqAccess<'de> for RowSeqAccess<'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        if self.index == self.row.values.len() {
+            return Ok(None);
+        }
+        let value = self.row.deserialize_column(self.index, seed)?;
+        self.index += 1;
+        Ok(Some(value))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.row.values.len() - self.index)
+    }
+}
+
+struct RowMapAccess<'de> {
+    row: RowDeserializer<'de>,
+    columns: &'de [Column],
+    index: usize,
+}
+
+impl<'de> MapAccess<'de> for RowMapAccess<'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        match self.columns.get(self.index) {
+            Some(column) => seed
+                .deserialize(MapKeyDeserializer(column.name()))
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let value = self.row.deserialize_column(self.index, seed)?;
+        self.index += 1;
+        Ok(value)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.columns.len() - self.index)
+    }
+}
+
+/// Map keys are always JSON strings, so keys of other types (like the `bigint` keys of
+/// a `map(bigint, varchar)`) are parsed from the string.
+struct MapKeyDeserializer<'de>(&'de str);
+
+macro_rules! deserialize_parsed_key {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                let value = match serde_json::from_str(self.0) {
+                    Ok(value @ (Value::Number(_) | Value::Bool(_))) => value,
+                    _ => Value::String(self.0.to_owned()),
+                };
+                value.$method(visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for MapKeyDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_borrowed_str(self.0)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        Value::String(self.0.to_owned()).deserialize_enum(name, variants, visitor)
+    }
+
+    deserialize_parsed_key! {
+        deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64
+        deserialize_i128 deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64
+        deserialize_u128 deserialize_f32 deserialize_f64
+    }
+
+    serde::forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct seq tuple tuple_struct map
+        struct identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::de::DeserializeOwned;
+    use serde::Deserialize;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    fn from_value<T: DeserializeOwned>(value: Value) -> Result<T, Error> {
+        T::deserialize(ValueDeserializer(&value))
+    }
+
+    #[test]
+    fn test_special_floats() {
+        let row: (f64, f64, f64, f32, f64) =
+            from_value(json!(["NaN", "Infinity", "-Infinity", "-Infinity", 1.5])).unwrap();
+        assert!(row.0.is_nan());
+        assert_eq!(row.1, f64::INFINITY);
+        assert_eq!(row.2, f64::NEG_INFINITY);
+        assert_eq!(row.3, f32::NEG_INFINITY);
+        assert_eq!(row.4, 1.5);
+
+        let row: (Option<f64>, Option<f32>) = from_value(json!(["Infinity", null])).unwrap();
+        assert_eq!(row, (Some(f64::INFINITY), None));
+
+        // Other strings are still errors, and strings are still strings.
+        assert!(from_value::<(f64,)>(json!(["nan"])).is_err());
+        let row: (String,) = from_value(json!(["NaN"])).unwrap();
+        assert_eq!(row.0, "NaN");
+    }
+
+    #[test]
+    fn test_nested_special_floats() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Inner {
+            x: f64,
+            y: Vec<Option<f32>>,
+        }
+
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Row {
+            array: Vec<f64>,
+            map: HashMap<String, f64>,
+            keyed_by_int: HashMap<i64, f64>,
+            inner: Inner,
+        }
+
+        let row: Row = from_value(json!([
+            [1.0, "Infinity"],
+            {"a": "-Infinity"},
+            {"1": "Infinity"},
+            ["-Infinity", [null, "Infinity"]],
+        ]))
+        .unwrap();
+        assert_eq!(
+            row,
+            Row {
+                array: vec![1.0, f64::INFINITY],
+                map: HashMap::from([("a".to_owned(), f64::NEG_INFINITY)]),
+                keyed_by_int: HashMap::from([(1, f64::INFINITY)]),
+                inner: Inner {
+                    x: f64::NEG_INFINITY,
+                    y: vec![None, Some(f32::INFINITY)],
+                },
+            }
+        );
+
+        // Rows deserialized by name are objects.
+        let inner: Inner = from_value(json!({"y": ["NaN"], "x": 0.5})).unwrap();
+        assert!(inner.y[0].unwrap().is_nan());
+        assert_eq!(inner.x, 0.5);
+    }
+
+    #[test]
+    fn test_mismatches() {
+        assert!(from_value::<(i64, i64)>(json!([1, 2, 3])).is_err());
+        assert!(from_value::<(i64, i64)>(json!([1])).is_err());
+        assert!(from_value::<(i64,)>(json!({"a": 1})).is_err());
+        assert!(from_value::<HashMap<i64, i64>>(json!({"a": 1})).is_err());
+        let value: Value = from_value(json!([{"a": ["NaN"]}])).unwrap();
+        assert_eq!(value, json!([{"a": ["NaN"]}]));
+    }
+}
@@ -0,0 +1,38 @@
+//! Runs a statement on async-std.  This is its own test binary because async-std
+//! reaches tokio (which reqwest needs) through the first tokio runtime it finds, and
+//! in the library's tests that would be the runtime of some other test, which is
+//! shut down when that test ends.
+#![cfg(feature = "async-std")]
+
+#[path = "common/fixtures.rs"]
+mod fixtures;
+
+use fixtures::finished_response;
+use prestino::PrestinoClient;
+use serde_json::json;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[test]
+fn test_async_std_runtime() {
+    let rows: Vec<(i64,)> = async_std::task::block_on(async {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/statement"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(finished_response(
+                    &mock_server.uri(),
+                    Some(json!([[1], [2]])),
+                )),
+            )
+            .mount(&mock_server)
+            .await;
+
+        PrestinoClient::trino(mock_server.uri())
+            .user("me")
+            .execute_collect("test")
+            .await
+    })
+    .unwrap();
+    assert_eq!(rows, vec![(1,), (2,)]);
+}
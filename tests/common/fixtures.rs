@@ -0,0 +1,32 @@
+use serde_json::{json, Value};
+
+/// A single response for a statement that finished immediately, with optional data.
+pub fn finished_response(base_uri: &str, data: Option<Value>) -> String {
+    let mut response = json!({
+        "id": uuid::Uuid::new_v4().to_string(),
+        "infoUri": format!("{base_uri}/ui/query.html"),
+        "stats": {
+            "state": "FINISHED",
+            "queued": false,
+            "scheduled": true,
+            "nodes": 1,
+            "totalSplits": 1,
+            "queuedSplits": 0,
+            "runningSplits": 0,
+            "completedSplits": 1,
+            "cpuTimeMillis": 0,
+            "wallTimeMillis": 0,
+            "queuedTimeMillis": 0,
+            "elapsedTimeMillis": 0,
+            "processedRows": 0,
+            "processedBytes": 0,
+            "physicalInputBytes": 0,
+            "peakMemoryBytes": 0,
+            "spilledBytes": 0
+        }
+    });
+    if let Some(data) = data {
+        response["data"] = data;
+    }
+    response.to_string()
+}
@@ -1,3 +1,5 @@
+#![allow(clippy::type_complexity)]
+
 mod common;
 use common::get_rows;
 use maplit::hashmap;
@@ -163,14 +165,14 @@ async fn test_basic_types_nullable_non_option() {
         get_rows(sql).await;
     match result {
         Ok(_) => panic!("Failed to error on incorrect type deserialization."),
-        Err(PrestinoError::HttpError(e)) => println!("Found right error: {e:?}"),
+        Err(PrestinoError::RowDecodeError(e)) => println!("Found right error: {e}"),
         Err(err) => panic!("Unexpected error: {err:?}"),
     }
 
     let result2: Result<Vec<BasicTypes>, PrestinoError> = get_rows(sql).await;
     match result2 {
         Ok(_) => panic!("Failed to error on incorrect type deserialization."),
-        Err(PrestinoError::HttpError(e)) => println!("Found right error: {e:?}"),
+        Err(PrestinoError::RowDecodeError(e)) => println!("Found right error: {e}"),
         Err(err) => panic!("Unexpected error: {err:?}"),
     }
 }
@@ -304,3 +306,84 @@ async fn test_struct_types() {
         }]
     );
 }
+
+#[cfg(feature = "bigdecimal")]
+#[test(tokio::test)]
+async fn test_bigdecimal_types() {
+    use bigdecimal::BigDecimal;
+    use std::str::FromStr;
+
+    let sql = r#"
+        SELECT
+            CAST('9999999999999999999999999999.9999999999' AS DECIMAL(38, 10)) AS max_dec,
+            CAST('-9999999999999999999999999999.9999999999' AS DECIMAL(38, 10)) AS min_dec,
+            CAST(NULL AS DECIMAL(10, 2)) AS null_dec
+    "#;
+
+    let rows: Vec<(BigDecimal, BigDecimal, Option<BigDecimal>)> = get_rows(sql).await.unwrap();
+    assert_eq!(
+        rows,
+        vec![(
+            BigDecimal::from_str("9999999999999999999999999999.9999999999").unwrap(),
+            BigDecimal::from_str("-9999999999999999999999999999.9999999999").unwrap(),
+            None,
+        )]
+    );
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct DecimalRow {
+        #[serde(with = "prestino::results::decimal::bigdecimal")]
+        max_dec: BigDecimal,
+        #[serde(with = "prestino::results::decimal::bigdecimal")]
+        min_dec: BigDecimal,
+        #[serde(with = "prestino::results::decimal::bigdecimal::option")]
+        null_dec: Option<BigDecimal>,
+    }
+    let rows: Vec<DecimalRow> = get_rows(sql).await.unwrap();
+    assert_eq!(
+        rows,
+        vec![DecimalRow {
+            max_dec: BigDecimal::from_str("9999999999999999999999999999.9999999999").unwrap(),
+            min_dec: BigDecimal::from_str("-9999999999999999999999999999.9999999999").unwrap(),
+            null_dec: None,
+        }]
+    );
+}
+
+#[cfg(feature = "rust_decimal")]
+#[test(tokio::test)]
+async fn test_rust_decimal_types() {
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct DecimalRow {
+        #[serde(with = "prestino::results::decimal::rust_decimal")]
+        a: Decimal,
+        #[serde(with = "prestino::results::decimal::rust_decimal::option")]
+        b: Option<Decimal>,
+    }
+
+    let sql = r#"
+        SELECT
+            CAST('-999999999999999999.9999999999' AS DECIMAL(38, 10)) AS a,
+            CAST(NULL AS DECIMAL(10, 2)) AS b
+    "#;
+    let rows: Vec<DecimalRow> = get_rows(sql).await.unwrap();
+    assert_eq!(
+        rows,
+        vec![DecimalRow {
+            a: Decimal::from_str("-999999999999999999.9999999999").unwrap(),
+            b: None,
+        }]
+    );
+
+    // More digits than a Decimal can hold is an error rather than a rounded value.
+    let sql = r#"
+        SELECT
+            CAST('9999999999999999999999999999.9999999999' AS DECIMAL(38, 10)) AS a,
+            CAST(NULL AS DECIMAL(10, 2)) AS b
+    "#;
+    let result: Result<Vec<DecimalRow>, PrestinoError> = get_rows(sql).await;
+    assert!(result.is_err());
+}